@@ -0,0 +1,44 @@
+//! Compare the linear `DeviceVmemMapper::lookup` with the table of `IndexedDeviceVmemMapper`
+//!
+//! Run on the host with `cargo bench --features mock --target <host triple>`.
+
+use armv7::structures::paging::{DeviceVmemMapper, IndexedDeviceVmemMapper};
+use armv7::{PhysicalAddress, VirtualAddress};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+static DEVICES: [u8; 32] = [
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x2d, 0x2e, 0x2f,
+];
+
+fn device_lookup(c: &mut Criterion) {
+    let base = VirtualAddress::new(0x4000_0000);
+    let linear = DeviceVmemMapper::new(base, &DEVICES).unwrap();
+    let indexed = IndexedDeviceVmemMapper::new(DeviceVmemMapper::new(base, &DEVICES).unwrap());
+    // Best and worst case of the linear search and an address without a device
+    let addrs = [
+        PhysicalAddress::new(0x1000_1000),
+        PhysicalAddress::new(0x2f00_1000),
+        PhysicalAddress::new(0xf000_1000),
+    ];
+    for &addr in addrs.iter() {
+        assert_eq!(
+            linear.lookup(addr).map(|virt| virt.as_u32()),
+            indexed.lookup(addr).map(|virt| virt.as_u32())
+        );
+    }
+
+    let mut group = c.benchmark_group("device_lookup");
+    for (name, &addr) in ["first", "last", "missing"].iter().zip(addrs.iter()) {
+        group.bench_function(format!("linear/{}", name), |b| {
+            b.iter(|| linear.lookup(black_box(addr)))
+        });
+        group.bench_function(format!("indexed/{}", name), |b| {
+            b.iter(|| indexed.lookup(black_box(addr)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, device_lookup);
+criterion_main!(benches);
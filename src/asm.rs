@@ -0,0 +1,141 @@
+//! Wrappers around single assembly instructions
+//!
+//! The barriers use the dedicated `dsb`, `dmb` and `isb` instructions. With the feature
+//! `legacy-barriers` the equivalent CP15 operations are used instead, which are also available on
+//! cores predating the armv7 barrier instructions.
+
+use crate::regs::program_state::{CPSR, PSR};
+use crate::regs::security::pending_irq;
+use core::sync::atomic::{compiler_fence, Ordering};
+use register::cpu::RegisterReadWrite;
+
+/// No operation
+#[inline]
+pub fn nop() {
+    unsafe {
+        llvm_asm!("nop" :::: "volatile");
+    }
+}
+
+/// Wait for event
+#[inline]
+pub fn wfe() {
+    unsafe {
+        llvm_asm!("wfe" :::: "volatile");
+    }
+}
+
+/// Wait for interrupt
+#[inline]
+pub fn wfi() {
+    unsafe {
+        llvm_asm!("wfi" :::: "volatile");
+    }
+}
+
+/// Wait for an interrupt without missing one that arrives just before going to sleep
+///
+/// IRQs are masked, the interrupt status register is checked for a pending IRQ and only if there
+/// is none `wfi` is executed. Afterwards the previous IRQ mask is restored, hence a pending IRQ is
+/// taken right after this function returns.
+///
+/// This is safe because the architecture wakes the core from `wfi` whenever an interrupt is
+/// pending, even if it is masked in the CPSR. An IRQ arriving between the check and the `wfi`
+/// therefore does not put the core to sleep indefinitely.
+#[inline]
+pub fn idle() {
+    let saved = CPSR.extract();
+    CPSR.modify(PSR::IRQ::Masked);
+    isb();
+    if !pending_irq() {
+        dsb();
+        wfi();
+    }
+    CPSR.modify(PSR::IRQ.val(saved.read(PSR::IRQ)));
+}
+
+/// Send event to all cores
+#[inline]
+pub fn sev() {
+    unsafe {
+        llvm_asm!("sev" :::: "volatile");
+    }
+}
+
+/// Signal that the core is in a spin-wait loop
+///
+/// This is only a hint, on a single-threaded core without hypervisor it behaves like a `nop`.
+#[inline]
+pub fn yield_now() {
+    unsafe {
+        llvm_asm!("yield" :::: "volatile");
+    }
+}
+
+/// Data synchronization barrier
+#[inline]
+pub fn dsb() {
+    #[cfg(not(feature = "legacy-barriers"))]
+    unsafe {
+        llvm_asm!("dsb" ::: "memory" : "volatile");
+    }
+    #[cfg(feature = "legacy-barriers")]
+    unsafe {
+        llvm_asm!("mcr p15, 0, $0, c7, c10, 4" :: "r"(0) : "memory" : "volatile");
+    }
+}
+
+/// Data memory barrier
+#[inline]
+pub fn dmb() {
+    #[cfg(not(feature = "legacy-barriers"))]
+    unsafe {
+        llvm_asm!("dmb" ::: "memory" : "volatile");
+    }
+    #[cfg(feature = "legacy-barriers")]
+    unsafe {
+        llvm_asm!("mcr p15, 0, $0, c7, c10, 5" :: "r"(0) : "memory" : "volatile");
+    }
+}
+
+/// Instruction synchronization barrier
+#[inline]
+pub fn isb() {
+    #[cfg(not(feature = "legacy-barriers"))]
+    unsafe {
+        llvm_asm!("isb" ::: "memory" : "volatile");
+    }
+    #[cfg(feature = "legacy-barriers")]
+    unsafe {
+        llvm_asm!("mcr p15, 0, $0, c7, c5, 4" :: "r"(0) : "memory" : "volatile");
+    }
+}
+
+/// Memory barrier with the strength required by `order`
+///
+/// * `Relaxed` emits nothing,
+/// * `Acquire`, `Release` and `AcqRel` emit a `dmb` together with a compiler fence of the same
+///   ordering,
+/// * `SeqCst` emits a `dsb`, which additionally waits for cache and TLB maintenance operations.
+#[inline]
+pub fn fence(order: Ordering) {
+    match order {
+        Ordering::Relaxed => {}
+        Ordering::Acquire => {
+            dmb();
+            compiler_fence(Ordering::Acquire);
+        }
+        Ordering::Release => {
+            compiler_fence(Ordering::Release);
+            dmb();
+        }
+        Ordering::SeqCst => {
+            compiler_fence(Ordering::SeqCst);
+            dsb();
+        }
+        _ => {
+            compiler_fence(Ordering::AcqRel);
+            dmb();
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Diagnostic output of the processor state
+//!
+//! A panic handler can print the state of the faulting core by
+//! ```
+//!     writeln!(uart, "{}", FaultContext);
+//! ```
+
+use crate::regs::fault_handling::*;
+use crate::regs::program_state::*;
+use crate::regs::vmem_control::{domain_access, DomainAccess};
+use crate::structures::paging::TranslationTable;
+use crate::VirtualAddress;
+use core::fmt;
+
+/// Describe a fault status in the short descriptor format
+///
+/// `status` is the combination of the FS4 bit and the FS bits, i.e. `(FS4 << 4) | FS`.
+pub fn fault_status_description(status: u32) -> &'static str {
+    match status {
+        0b00001 => "Alignment fault",
+        0b00010 => "Debug event",
+        0b00011 => "Access flag fault (section)",
+        0b00100 => "Fault on instruction cache maintenance",
+        0b00101 => "Translation fault (section)",
+        0b00110 => "Access flag fault (page)",
+        0b00111 => "Translation fault (page)",
+        0b01000 => "Synchronous external abort",
+        0b01001 => "Domain fault (section)",
+        0b01011 => "Domain fault (page)",
+        0b01100 => "Synchronous external abort on translation table walk (first level)",
+        0b01101 => "Permission fault (section)",
+        0b01110 => "Synchronous external abort on translation table walk (second level)",
+        0b01111 => "Permission fault (page)",
+        0b10100 => "Lockdown abort",
+        0b10110 => "Asynchronous external abort",
+        0b11000 => "Asynchronous parity error on memory access",
+        0b11001 => "Synchronous parity error on memory access",
+        0b11010 => "Coprocessor abort",
+        0b11100 => "Synchronous parity error on translation table walk (first level)",
+        0b11110 => "Synchronous parity error on translation table walk (second level)",
+        _ => "Unknown fault",
+    }
+}
+
+/// Print the fault registers, the program state and the active translation table
+pub fn dump_fault_context(f: &mut fmt::Formatter) -> fmt::Result {
+    let dfsr = DFSR.extract();
+    let dfs = (dfsr.read(DFS::FS4) << 4) | dfsr.read(DFS::FS);
+    let access = if dfsr.is_set(DFS::WNR) {
+        "write"
+    } else {
+        "read"
+    };
+    let external = if dfsr.is_set(DFS::EXT) {
+        ", external"
+    } else {
+        ""
+    };
+    writeln!(f, "Data fault at {:#010x}", DFAR.get())?;
+    writeln!(
+        f,
+        "  DFSR {:#010x}: {} (domain {}, {}{})",
+        DFSR.get(),
+        fault_status_description(dfs),
+        dfsr.read(DFS::DOMAIN),
+        access,
+        external
+    )?;
+    let ifsr = IFSR.extract();
+    let ifs = (ifsr.read(IFS::FS4) << 4) | ifsr.read(IFS::FS);
+    writeln!(f, "Prefetch fault at {:#010x}", IFAR.get())?;
+    writeln!(
+        f,
+        "  IFSR {:#010x}: {}",
+        IFSR.get(),
+        fault_status_description(ifs)
+    )?;
+    write!(f, "CPSR {:#010x}: ", CPSR.get())?;
+    match get_current_mode() {
+        Some(mode) => writeln!(f, "{}", mode)?,
+        None => writeln!(f, "Invalid mode {:#07b}", current_mode_raw())?,
+    }
+    writeln!(
+        f,
+        "TTBR0 at {:#010x}",
+        TranslationTable::get_ttbr0_phys().as_u32()
+    )
+}
+
+/// Printable snapshot of the fault context, see `dump_fault_context`
+pub struct FaultContext;
+
+impl fmt::Display for FaultContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump_fault_context(f)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Banked stack pointer and link register of a processor mode
+pub struct BankedRegisters {
+    pub sp: VirtualAddress,
+    pub lr: VirtualAddress,
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Stack pointers and link registers of all privileged exception modes
+pub struct ModeStacks {
+    pub svc: BankedRegisters,
+    pub irq: BankedRegisters,
+    pub fiq: BankedRegisters,
+    pub abt: BankedRegisters,
+    pub und: BankedRegisters,
+}
+
+/// Read the banked stack pointer and link register of a mode
+///
+/// The banked `mrs` instructions require the virtualization extensions, hence this switches to the
+/// mode with IRQs and FIQs masked and back within a single assembly block. The stack is not touched
+/// while the processor is in the other mode. The operands are kept in r0 to r3, because r8 to r12
+/// are banked in `FIQ` mode.
+fn read_banked(mode: PSR::MODE::Value) -> BankedRegisters {
+    let sp: u32;
+    let lr: u32;
+    let _saved: u32;
+    let _scratch: u32;
+    unsafe {
+        llvm_asm!("mrs $2, cpsr
+                   bic $0, $2, #0x1f
+                   orr $3, $3, $0
+                   orr $3, $3, #0xc0
+                   msr cpsr_c, $3
+                   mov $0, sp
+                   mov $1, lr
+                   msr cpsr_c, $2"
+                  : "={r0}"(sp), "={r1}"(lr), "={r2}"(_saved), "={r3}"(_scratch)
+                  : "3"(mode as u32)
+                  : "memory"
+                  : "volatile");
+    }
+    BankedRegisters {
+        sp: VirtualAddress::new(sp),
+        lr: VirtualAddress::new(lr),
+    }
+}
+
+/// Capture the stack pointers and link registers of all privileged exception modes
+///
+/// Has to be called from a privileged mode, in USR mode all entries contain the registers of USR.
+pub fn capture_mode_stacks() -> ModeStacks {
+    ModeStacks {
+        svc: read_banked(PSR::MODE::Value::SVC),
+        irq: read_banked(PSR::MODE::Value::IRQ),
+        fiq: read_banked(PSR::MODE::Value::FIQ),
+        abt: read_banked(PSR::MODE::Value::ABT),
+        und: read_banked(PSR::MODE::Value::UND),
+    }
+}
+
+/// Kind of a data abort in the short descriptor format
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FaultKind {
+    Alignment,
+    Translation,
+    AccessFlag,
+    Domain,
+    Permission,
+    External,
+    /// Any other fault status
+    Other(u32),
+}
+
+impl FaultKind {
+    /// Classify a fault status, the combination of the FS4 bit and the FS bits
+    pub fn from_status(status: u32) -> Self {
+        match status {
+            0b00001 => FaultKind::Alignment,
+            0b00101 | 0b00111 => FaultKind::Translation,
+            0b00011 | 0b00110 => FaultKind::AccessFlag,
+            0b01001 | 0b01011 => FaultKind::Domain,
+            0b01101 | 0b01111 => FaultKind::Permission,
+            0b01000 | 0b01100 | 0b01110 | 0b10110 => FaultKind::External,
+            status => FaultKind::Other(status),
+        }
+    }
+}
+
+/// Structured description of the last data abort
+#[derive(Copy, Clone, Debug)]
+pub struct AbortExplanation {
+    /// Faulting address from the DFAR
+    pub addr: VirtualAddress,
+    pub kind: FaultKind,
+    /// Fault status from the DFSR
+    pub status: u32,
+    /// Domain of the access, only valid for domain and permission faults
+    pub domain: u8,
+    /// Access of the domain as currently configured in the DACR
+    pub domain_access: DomainAccess,
+    /// The fault was caused by a write
+    pub write: bool,
+}
+
+impl AbortExplanation {
+    /// Returns whether the fault could be resolved by making the domain a manager domain
+    ///
+    /// This is the case for domain faults and for permission faults in client domains.
+    pub fn resolvable_by_domain(&self) -> bool {
+        match self.kind {
+            FaultKind::Domain => true,
+            FaultKind::Permission => self.domain_access == DomainAccess::Client,
+            _ => false,
+        }
+    }
+}
+
+/// Decode the DFSR and DFAR of the last data abort
+pub fn explain_data_abort() -> AbortExplanation {
+    let status = DFSR.fault_status();
+    let domain = DFSR.domain();
+    AbortExplanation {
+        addr: VirtualAddress::new(DFAR.get()),
+        kind: FaultKind::from_status(status),
+        status,
+        domain,
+        // The domain field of the DFSR has four bits, hence the domain is always in range
+        domain_access: domain_access(domain).unwrap_or(DomainAccess::NoAccess),
+        write: DFSR.is_set(DFS::WNR),
+    }
+}
+
+impl fmt::Display for AbortExplanation {
+    /// Formats the explanation as e.g. `Permission fault (section) on write to 0x80001000 in
+    /// domain 3`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = if self.write { "write" } else { "read" };
+        write!(
+            f,
+            "{} on {} to {:#010x}",
+            fault_status_description(self.status),
+            access,
+            self.addr.as_u32()
+        )?;
+        match self.kind {
+            FaultKind::Domain | FaultKind::Permission => {
+                write!(f, " in domain {} ({:?})", self.domain, self.domain_access)
+            }
+            _ => Ok(()),
+        }
+    }
+}
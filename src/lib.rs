@@ -4,15 +4,25 @@
 #![feature(stdsimd)]
 #![no_std]
 #![feature(const_fn)]
+#![cfg_attr(feature = "error-in-core", feature(error_in_core))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use crate::structures::paging::PageError;
+use crate::util::{HexDoubleWord, HexWord};
+use core::convert::TryFrom;
 use core::fmt;
-use core::ops::{Add, AddAssign, BitOr, Sub, SubAssign};
+use core::ops::{Add, AddAssign, BitAnd, BitAndAssign, BitOr, Sub, SubAssign};
 
-//pub mod asm;
+pub mod asm;
+pub mod diagnostics;
 pub mod regs;
 pub mod structures;
+pub mod sync;
+pub mod util;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone)]
 #[repr(transparent)]
 pub struct VirtualAddress(u32);
 
@@ -21,6 +31,35 @@ impl VirtualAddress {
     pub const fn new(addr: u32) -> VirtualAddress {
         VirtualAddress(addr)
     }
+    /// Create the null address
+    pub const fn null() -> VirtualAddress {
+        VirtualAddress(0)
+    }
+    /// Create a new address that is a multiple of `align`
+    ///
+    /// Fails with `PageError::AlignError` if it is not or if `align` is zero.
+    pub fn new_aligned(addr: u32, align: u32) -> Result<VirtualAddress, PageError> {
+        match addr.checked_rem(align) {
+            Some(0) => Ok(VirtualAddress(addr)),
+            _ => Err(PageError::AlignError { addr, align }),
+        }
+    }
+    /// Create a new address aligned to 4KB
+    pub fn new_page_aligned(addr: u32) -> Result<VirtualAddress, PageError> {
+        Self::new_aligned(addr, structures::paging::PAGE_SIZE)
+    }
+    /// Create a new address, returns `None` for the null address
+    pub fn new_nonzero(addr: u32) -> Option<VirtualAddress> {
+        if addr == 0 {
+            None
+        } else {
+            Some(VirtualAddress(addr))
+        }
+    }
+    /// Checks whether the address is the null address
+    pub const fn is_null(self) -> bool {
+        self.0 == 0
+    }
     /// Create a virtual address from the indices of a translation table and a page table and the
     /// offset
     ///
@@ -47,12 +86,78 @@ impl VirtualAddress {
         address |= offset;
         Some(VirtualAddress(address))
     }
+    /// Create a virtual address from the indices of a translation table and a page table and the
+    /// offset in a constant context
+    ///
+    /// The caller has to make sure that `translation_index` is smaller than 4096, `page_index` is
+    /// smaller than 256 and `offset` is smaller than 4096, otherwise the indices overlap.
+    pub const fn from_indices_unchecked(
+        translation_index: usize,
+        page_index: usize,
+        offset: u32,
+    ) -> VirtualAddress {
+        VirtualAddress(((translation_index as u32) << 20) | ((page_index as u32) << 12) | offset)
+    }
     /// Calculate for a virtual address the index in the base table
     pub const fn translation_table_index(self) -> usize {
         // Divide by 1Mb
         let base_addr = self.0 >> 20;
         base_addr as usize
     }
+    /// Determine which translation table base register is used to translate the address
+    ///
+    /// `n` is the value of TTBCR.N, only the lowest three bits are used. If the top `n` bits of
+    /// the address are zero it is translated by TTBR0, otherwise by TTBR1.
+    pub fn which_ttbr(self, n: u8) -> structures::paging::Ttbr {
+        use structures::paging::Ttbr;
+        let n = n & 0b111;
+        if n == 0 || (self.0 >> (32 - n as u32)) == 0 {
+            Ttbr::Ttbr0
+        } else {
+            Ttbr::Ttbr1
+        }
+    }
+    /// Calculate the index in the base table selected by `which_ttbr`
+    ///
+    /// The TTBR0 table only has `4096 >> n` entries, the TTBR1 table is always indexed as a full
+    /// sized table.
+    pub fn base_table_index_for(self, n: u8) -> usize {
+        let n = n & 0b111;
+        match self.which_ttbr(n) {
+            structures::paging::Ttbr::Ttbr0 => self.translation_table_index() & ((4096 >> n) - 1),
+            structures::paging::Ttbr::Ttbr1 => self.translation_table_index(),
+        }
+    }
+    /// Index in the base table as a typed index
+    pub fn table_index(self) -> structures::paging::TableIndex {
+        structures::paging::TableIndex::from(self)
+    }
+    /// Index in a page table as a typed index
+    pub fn page_index(self) -> structures::paging::PageIndex {
+        structures::paging::PageIndex::from(self)
+    }
+    /// Base address of the page containing the address
+    pub const fn page_base(self) -> VirtualAddress {
+        VirtualAddress(self.0 & !(structures::paging::PAGE_SIZE - 1))
+    }
+    /// Base address of the section containing the address
+    pub const fn section_base(self) -> VirtualAddress {
+        VirtualAddress(self.0 & !(structures::paging::SECTION_SIZE - 1))
+    }
+    /// The 4KB page containing the address
+    pub const fn containing_page(self) -> VirtualAddressRange {
+        VirtualAddressRange {
+            start: self.page_base(),
+            len: structures::paging::PAGE_SIZE,
+        }
+    }
+    /// The 1MB section containing the address
+    pub const fn containing_section(self) -> VirtualAddressRange {
+        VirtualAddressRange {
+            start: self.section_base(),
+            len: structures::paging::SECTION_SIZE,
+        }
+    }
     /// Calculate the index in a page table
     pub const fn page_table_index(self) -> usize {
         let page_addr = (self.0 & 0xfffff) >> 12;
@@ -66,6 +171,32 @@ impl VirtualAddress {
     pub fn as_u32(self) -> u32 {
         self.0
     }
+    /// Subtract an offset from the address, returns `None` on underflow
+    pub fn checked_sub(self, rhs: u32) -> Option<Self> {
+        self.0.checked_sub(rhs).map(Self::new)
+    }
+    /// Advance the address by `step` in place
+    ///
+    /// Returns `false` and leaves the address unchanged if the addition would overflow, so that
+    /// loops like `while addr.try_advance(PAGE_SIZE) && addr < end` terminate at the top of the
+    /// address space.
+    pub fn try_advance(&mut self, step: u32) -> bool {
+        match self.0.checked_add(step) {
+            Some(addr) => {
+                self.0 = addr;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Distance between two addresses regardless of their order
+    pub fn abs_diff(self, other: Self) -> u32 {
+        if self.0 >= other.0 {
+            self.0 - other.0
+        } else {
+            other.0 - self.0
+        }
+    }
     /// Create a virtual address from a pointer
     pub fn from_ptr<T>(ptr: *const T) -> Self {
         Self::new(ptr as usize as u32)
@@ -78,6 +209,16 @@ impl VirtualAddress {
     pub fn from_ext_fn(fn_ptr: extern "C" fn() -> !) -> Self {
         Self::new(fn_ptr as usize as u32)
     }
+    /// Converts the address to a pointer sized integer
+    #[cfg(target_pointer_width = "32")]
+    pub const fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+    /// Create an address from a pointer sized integer
+    #[cfg(target_pointer_width = "32")]
+    pub const fn from_usize(addr: usize) -> Self {
+        VirtualAddress(addr as u32)
+    }
     /// Converts the address to a raw pointer
     #[cfg(target_pointer_width = "32")]
     pub fn as_ptr<T>(self) -> *const T {
@@ -110,6 +251,19 @@ impl BitOr<u32> for VirtualAddress {
     }
 }
 
+impl BitAnd<u32> for VirtualAddress {
+    type Output = Self;
+    fn bitand(self, rhs: u32) -> Self::Output {
+        VirtualAddress::new(self.0 & rhs)
+    }
+}
+
+impl BitAndAssign<u32> for VirtualAddress {
+    fn bitand_assign(&mut self, rhs: u32) {
+        *self = *self & rhs;
+    }
+}
+
 #[cfg(target_pointer_width = "32")]
 impl Add<usize> for VirtualAddress {
     type Output = Self;
@@ -125,10 +279,11 @@ impl AddAssign<usize> for VirtualAddress {
     }
 }
 
+/// Subtraction wraps around on underflow, use `checked_sub` to detect it
 impl Sub<u32> for VirtualAddress {
     type Output = Self;
     fn sub(self, rhs: u32) -> Self::Output {
-        VirtualAddress::new(self.0.checked_sub(rhs).unwrap())
+        VirtualAddress::new(self.0.wrapping_sub(rhs))
     }
 }
 
@@ -153,10 +308,19 @@ impl SubAssign<usize> for VirtualAddress {
     }
 }
 
+/// The difference wraps around if `rhs` is larger than `self`
 impl Sub<VirtualAddress> for VirtualAddress {
     type Output = u32;
     fn sub(self, rhs: VirtualAddress) -> Self::Output {
-        self.as_u32().checked_sub(rhs.as_u32()).unwrap()
+        self.as_u32().wrapping_sub(rhs.as_u32())
+    }
+}
+
+impl fmt::Debug for VirtualAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VirtualAddress")
+            .field(&HexWord(self.0))
+            .finish()
     }
 }
 
@@ -167,7 +331,14 @@ impl fmt::LowerHex for VirtualAddress {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl fmt::Pointer for VirtualAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ptr = self.0 as usize as *const ();
+        fmt::Pointer::fmt(&ptr, f)
+    }
+}
+
+#[derive(Copy, Clone)]
 #[repr(transparent)]
 pub struct PhysicalAddress(u32);
 
@@ -176,14 +347,87 @@ impl PhysicalAddress {
     pub const fn new(addr: u32) -> PhysicalAddress {
         PhysicalAddress(addr)
     }
+    /// Create the null address
+    pub const fn null() -> PhysicalAddress {
+        PhysicalAddress(0)
+    }
+    /// Create a new address that is a multiple of `align`
+    ///
+    /// Fails with `PageError::AlignError` if it is not or if `align` is zero.
+    pub fn new_aligned(addr: u32, align: u32) -> Result<PhysicalAddress, PageError> {
+        match addr.checked_rem(align) {
+            Some(0) => Ok(PhysicalAddress(addr)),
+            _ => Err(PageError::AlignError { addr, align }),
+        }
+    }
+    /// Create a new address aligned to 4KB
+    pub fn new_page_aligned(addr: u32) -> Result<PhysicalAddress, PageError> {
+        Self::new_aligned(addr, structures::paging::PAGE_SIZE)
+    }
+    /// Create a new address, returns `None` for the null address
+    pub fn new_nonzero(addr: u32) -> Option<PhysicalAddress> {
+        if addr == 0 {
+            None
+        } else {
+            Some(PhysicalAddress(addr))
+        }
+    }
+    /// Checks whether the address is the null address
+    pub const fn is_null(self) -> bool {
+        self.0 == 0
+    }
     /// Converts the address to an unsigned integer
     pub const fn as_u32(self) -> u32 {
         self.0
     }
+    /// Subtract an offset from the address, returns `None` on underflow
+    pub fn checked_sub(self, rhs: u32) -> Option<Self> {
+        self.0.checked_sub(rhs).map(Self::new)
+    }
+    /// Advance the address by `step` in place
+    ///
+    /// Returns `false` and leaves the address unchanged if the addition would overflow, so that
+    /// loops like `while addr.try_advance(PAGE_SIZE) && addr < end` terminate at the top of the
+    /// address space.
+    pub fn try_advance(&mut self, step: u32) -> bool {
+        match self.0.checked_add(step) {
+            Some(addr) => {
+                self.0 = addr;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Distance between two addresses regardless of their order
+    pub fn abs_diff(self, other: Self) -> u32 {
+        if self.0 >= other.0 {
+            self.0 - other.0
+        } else {
+            other.0 - self.0
+        }
+    }
+    /// Base address of the frame containing the address
+    pub const fn frame_base(self) -> PhysicalAddress {
+        PhysicalAddress(self.0 & !(structures::paging::PAGE_SIZE - 1))
+    }
+    /// Base address of the section containing the address
+    pub const fn section_base(self) -> PhysicalAddress {
+        PhysicalAddress(self.0 & !(structures::paging::SECTION_SIZE - 1))
+    }
     /// Create a physical address from a pointer
     pub fn from_ptr<T>(ptr: *const T) -> Self {
         Self::new(ptr as usize as u32)
     }
+    /// Converts the address to a pointer sized integer
+    #[cfg(target_pointer_width = "32")]
+    pub const fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+    /// Create an address from a pointer sized integer
+    #[cfg(target_pointer_width = "32")]
+    pub const fn from_usize(addr: usize) -> Self {
+        PhysicalAddress(addr as u32)
+    }
     /// Converts the address to a raw pointer
     #[cfg(target_pointer_width = "32")]
     pub fn as_ptr<T>(self) -> *const T {
@@ -228,10 +472,11 @@ impl AddAssign<usize> for PhysicalAddress {
     }
 }
 
+/// Subtraction wraps around on underflow, use `checked_sub` to detect it
 impl Sub<u32> for PhysicalAddress {
     type Output = Self;
     fn sub(self, rhs: u32) -> Self::Output {
-        PhysicalAddress::new(self.0.checked_sub(rhs).unwrap())
+        PhysicalAddress::new(self.0.wrapping_sub(rhs))
     }
 }
 
@@ -256,10 +501,32 @@ impl SubAssign<usize> for PhysicalAddress {
     }
 }
 
+/// The difference wraps around if `rhs` is larger than `self`
 impl Sub<PhysicalAddress> for PhysicalAddress {
     type Output = u32;
     fn sub(self, rhs: PhysicalAddress) -> Self::Output {
-        self.as_u32().checked_sub(rhs.as_u32()).unwrap()
+        self.as_u32().wrapping_sub(rhs.as_u32())
+    }
+}
+
+impl BitAnd<u32> for PhysicalAddress {
+    type Output = Self;
+    fn bitand(self, rhs: u32) -> Self::Output {
+        PhysicalAddress::new(self.0 & rhs)
+    }
+}
+
+impl BitAndAssign<u32> for PhysicalAddress {
+    fn bitand_assign(&mut self, rhs: u32) {
+        *self = *self & rhs;
+    }
+}
+
+impl fmt::Debug for PhysicalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PhysicalAddress")
+            .field(&HexWord(self.0))
+            .finish()
     }
 }
 
@@ -269,3 +536,340 @@ impl fmt::LowerHex for PhysicalAddress {
         fmt::LowerHex::fmt(&val, f)
     }
 }
+
+impl fmt::Pointer for PhysicalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ptr = self.0 as usize as *const ();
+        fmt::Pointer::fmt(&ptr, f)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+/// Physical address with up to 40 bits, as used by supersections and the Large Physical Address
+/// Extension
+pub struct PhysicalAddress64(u64);
+
+impl PhysicalAddress64 {
+    /// Create a new physical address from an unsigned integer
+    pub const fn new(addr: u64) -> PhysicalAddress64 {
+        PhysicalAddress64(addr)
+    }
+    /// Converts the address to an unsigned integer
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+    /// Bits 32 and higher of the address
+    pub const fn high_bits(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+    /// Subtract from the address, returns `None` on underflow
+    pub fn checked_sub(self, rhs: u64) -> Option<PhysicalAddress64> {
+        self.0.checked_sub(rhs).map(PhysicalAddress64)
+    }
+}
+
+impl From<PhysicalAddress> for PhysicalAddress64 {
+    fn from(addr: PhysicalAddress) -> Self {
+        PhysicalAddress64(addr.0 as u64)
+    }
+}
+
+/// Fails if the address does not fit into 32 bits
+impl TryFrom<PhysicalAddress64> for PhysicalAddress {
+    type Error = PageError;
+    fn try_from(addr: PhysicalAddress64) -> Result<Self, Self::Error> {
+        if addr.high_bits() != 0 {
+            return Err(PageError::SizeError);
+        }
+        Ok(PhysicalAddress(addr.0 as u32))
+    }
+}
+
+impl Add<u64> for PhysicalAddress64 {
+    type Output = Self;
+    fn add(self, rhs: u64) -> Self::Output {
+        PhysicalAddress64::new(self.0 + rhs)
+    }
+}
+
+impl AddAssign<u64> for PhysicalAddress64 {
+    fn add_assign(&mut self, rhs: u64) {
+        *self = *self + rhs;
+    }
+}
+
+/// Subtraction wraps around on underflow, use `checked_sub` to detect it
+impl Sub<u64> for PhysicalAddress64 {
+    type Output = Self;
+    fn sub(self, rhs: u64) -> Self::Output {
+        PhysicalAddress64::new(self.0.wrapping_sub(rhs))
+    }
+}
+
+impl SubAssign<u64> for PhysicalAddress64 {
+    fn sub_assign(&mut self, rhs: u64) {
+        *self = *self - rhs;
+    }
+}
+
+/// The difference wraps around if `rhs` is larger than `self`
+impl Sub<PhysicalAddress64> for PhysicalAddress64 {
+    type Output = u64;
+    fn sub(self, rhs: PhysicalAddress64) -> Self::Output {
+        self.0.wrapping_sub(rhs.0)
+    }
+}
+
+impl fmt::Debug for PhysicalAddress64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PhysicalAddress64")
+            .field(&HexDoubleWord(self.0))
+            .finish()
+    }
+}
+
+impl fmt::LowerHex for PhysicalAddress64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let val = self.0;
+        fmt::LowerHex::fmt(&val, f)
+    }
+}
+
+/// Range of virtual addresses from `start` up to, but not including, `start + len`
+///
+/// The range may extend to the end of the address space, i.e. `start + len` may be 2^32.
+#[derive(Copy, Clone, Debug)]
+pub struct VirtualAddressRange {
+    start: VirtualAddress,
+    len: u32,
+}
+
+impl VirtualAddressRange {
+    /// Create the range `start..end`, returns `None` if `end` is smaller than `start`
+    pub fn new(start: VirtualAddress, end: VirtualAddress) -> Option<Self> {
+        let len = end.0.checked_sub(start.0)?;
+        Some(VirtualAddressRange { start, len })
+    }
+    /// Create the range starting at `start` with `len` bytes
+    ///
+    /// Returns `None` if the range does not fit into the address space.
+    pub fn from_start_len(start: VirtualAddress, len: u32) -> Option<Self> {
+        if start.0 as u64 + len as u64 > 1 << 32 {
+            return None;
+        }
+        Some(VirtualAddressRange { start, len })
+    }
+    /// First address of the range
+    pub fn start(&self) -> VirtualAddress {
+        self.start
+    }
+    /// Address following the range, `None` if the range extends to the end of the address space
+    pub fn end(&self) -> Option<VirtualAddress> {
+        self.start.0.checked_add(self.len).map(VirtualAddress)
+    }
+    /// Number of bytes in the range
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+    /// Checks whether the range is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Checks whether the address is in the range
+    pub fn contains(&self, addr: VirtualAddress) -> bool {
+        addr.0 >= self.start.0 && addr.0 - self.start.0 < self.len
+    }
+}
+
+/// Range of physical addresses from `start` up to, but not including, `start + len`
+///
+/// The range may extend to the end of the address space, i.e. `start + len` may be 2^32.
+#[derive(Copy, Clone, Debug)]
+pub struct PhysicalAddressRange {
+    start: PhysicalAddress,
+    len: u32,
+}
+
+impl PhysicalAddressRange {
+    /// Create the range `start..end`, returns `None` if `end` is smaller than `start`
+    pub fn new(start: PhysicalAddress, end: PhysicalAddress) -> Option<Self> {
+        let len = end.0.checked_sub(start.0)?;
+        Some(PhysicalAddressRange { start, len })
+    }
+    /// Create the range starting at `start` with `len` bytes
+    ///
+    /// Returns `None` if the range does not fit into the address space.
+    pub fn from_start_len(start: PhysicalAddress, len: u32) -> Option<Self> {
+        if start.0 as u64 + len as u64 > 1 << 32 {
+            return None;
+        }
+        Some(PhysicalAddressRange { start, len })
+    }
+    /// First address of the range
+    pub fn start(&self) -> PhysicalAddress {
+        self.start
+    }
+    /// Address following the range, `None` if the range extends to the end of the address space
+    pub fn end(&self) -> Option<PhysicalAddress> {
+        self.start.0.checked_add(self.len).map(PhysicalAddress)
+    }
+    /// Number of bytes in the range
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+    /// Checks whether the range is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Checks whether the address is in the range
+    pub fn contains(&self, addr: PhysicalAddress) -> bool {
+        addr.0 >= self.start.0 && addr.0 - self.start.0 < self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn range_empty() {
+        let addr = VirtualAddress::new(0x8000_0000);
+        assert!(VirtualAddressRange::new(addr, addr - 1u32).is_none());
+        let range = VirtualAddressRange::new(addr, addr).unwrap();
+        assert!(range.is_empty());
+        assert_eq!(range.len(), 0);
+        assert!(!range.contains(addr));
+
+        let addr = PhysicalAddress::new(0x8000_0000);
+        assert!(PhysicalAddressRange::new(addr, addr - 1u32).is_none());
+        let range = PhysicalAddressRange::new(addr, addr).unwrap();
+        assert!(range.is_empty());
+        assert!(!range.contains(addr));
+    }
+
+    #[test]
+    fn range_full_address_space() {
+        let range = VirtualAddressRange::from_start_len(VirtualAddress::new(0), u32::MAX).unwrap();
+        assert_eq!(range.len(), u32::MAX);
+        assert!(!range.is_empty());
+        assert_eq!(range.end().map(|end| end.as_u32()), Some(u32::MAX));
+        assert!(range.contains(VirtualAddress::new(0)));
+        assert!(!range.contains(VirtualAddress::new(u32::MAX)));
+
+        // A range reaching 2^32 has no end address
+        let range =
+            VirtualAddressRange::from_start_len(VirtualAddress::new(0x1000), u32::MAX - 0xfff)
+                .unwrap();
+        assert!(range.end().is_none());
+        assert!(range.contains(VirtualAddress::new(u32::MAX)));
+
+        let range =
+            PhysicalAddressRange::from_start_len(PhysicalAddress::new(0), u32::MAX).unwrap();
+        assert_eq!(range.len(), u32::MAX);
+        assert!(range.contains(PhysicalAddress::new(u32::MAX - 1)));
+    }
+
+    #[test]
+    fn range_overflow() {
+        assert!(
+            VirtualAddressRange::from_start_len(VirtualAddress::new(0x1001), u32::MAX - 0xfff)
+                .is_none()
+        );
+        assert!(VirtualAddressRange::from_start_len(VirtualAddress::new(u32::MAX), 2).is_none());
+        assert!(PhysicalAddressRange::from_start_len(PhysicalAddress::new(u32::MAX), 2).is_none());
+        let range = VirtualAddressRange::from_start_len(VirtualAddress::new(u32::MAX), 1).unwrap();
+        assert_eq!(range.len(), 1);
+        assert!(range.contains(VirtualAddress::new(u32::MAX)));
+    }
+
+    #[test]
+    fn abs_diff_symmetric() {
+        let pairs = [
+            (0, 0),
+            (0, u32::MAX),
+            (u32::MAX, u32::MAX),
+            (0x1000, 0x8000_0000),
+            (0x8000_0000, 0x7fff_ffff),
+        ];
+        for &(a, b) in pairs.iter() {
+            let (va, vb) = (VirtualAddress::new(a), VirtualAddress::new(b));
+            assert_eq!(va.abs_diff(vb), vb.abs_diff(va));
+            let (pa, pb) = (PhysicalAddress::new(a), PhysicalAddress::new(b));
+            assert_eq!(pa.abs_diff(pb), pb.abs_diff(pa));
+            assert_eq!(va.abs_diff(vb), pa.abs_diff(pb));
+        }
+        let zero = VirtualAddress::new(0);
+        let max = VirtualAddress::new(u32::MAX);
+        assert_eq!(zero.abs_diff(max), u32::MAX);
+        assert_eq!(max.abs_diff(zero), u32::MAX);
+        assert_eq!(max.abs_diff(max), 0);
+        let zero = PhysicalAddress::new(0);
+        let max = PhysicalAddress::new(u32::MAX);
+        assert_eq!(zero.abs_diff(max), u32::MAX);
+        assert_eq!(max.abs_diff(zero), u32::MAX);
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn pointer_round_trip() {
+        let value = 0u32;
+        let ptr: *const u32 = &value;
+        assert_eq!(VirtualAddress::from_ptr(ptr).as_ptr::<u32>(), ptr);
+        assert_eq!(PhysicalAddress::from_ptr(ptr).as_ptr::<u32>(), ptr);
+
+        let mut value = 0u32;
+        let ptr: *mut u32 = &mut value;
+        assert_eq!(VirtualAddress::from_mut_ptr(ptr).as_mut_ptr::<u32>(), ptr);
+        assert_eq!(PhysicalAddress::from_ptr(ptr).as_mut_ptr::<u32>(), ptr);
+
+        let addr = VirtualAddress::new(0x8000_1000);
+        assert_eq!(
+            VirtualAddress::from_ptr(addr.as_ptr::<u8>()).as_u32(),
+            0x8000_1000
+        );
+        assert_eq!(format!("{:p}", addr), format!("{:p}", addr.as_ptr::<u8>()));
+        let addr = PhysicalAddress::new(0x8000_1000);
+        assert_eq!(format!("{:p}", addr), format!("{:p}", addr.as_ptr::<u8>()));
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn ext_fn_round_trip() {
+        extern "C" fn entry() -> ! {
+            unreachable!()
+        }
+        let fn_ptr: extern "C" fn() -> ! = entry;
+        let addr = VirtualAddress::from_ext_fn(fn_ptr);
+        assert_eq!(addr.as_ptr::<()>(), fn_ptr as *const ());
+        let addr = PhysicalAddress::from_ext_fn(fn_ptr);
+        assert_eq!(addr.as_ptr::<()>(), fn_ptr as *const ());
+    }
+
+    #[test]
+    fn debug_is_hex() {
+        assert_eq!(
+            format!("{:?}", VirtualAddress::new(0x8000_8000)),
+            "VirtualAddress(0x8000_8000)"
+        );
+        assert_eq!(
+            format!("{:?}", PhysicalAddress::new(0x1000)),
+            "PhysicalAddress(0x0000_1000)"
+        );
+        assert_eq!(
+            format!("{:?}", PhysicalAddress64::new(0x8000_1000)),
+            "PhysicalAddress64(0x8000_1000)"
+        );
+        assert_eq!(
+            format!("{:?}", PhysicalAddress64::new(0x12_3456_7890)),
+            "PhysicalAddress64(0x12_3456_7890)"
+        );
+        assert_eq!(
+            format!("{:?}", PhysicalAddress64::new(0xff_0000_0000)),
+            "PhysicalAddress64(0xff_0000_0000)"
+        );
+    }
+}
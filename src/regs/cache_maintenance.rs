@@ -0,0 +1,110 @@
+//! Register access to the cache and branch predictor maintenance operations
+//!
+//! Functional group according to the ARM ARM
+//!
+//! # Usage examples
+//! Invalidate the whole instruction cache
+//! ```
+//!     ICIALLU.set(0);
+//! ```
+//! Clean a data cache line to the point of coherency
+//! ```
+//!     DCCMVAC.set(virt_addr.as_u32());
+//! ```
+
+pub use register::cpu::RegisterWriteOnly;
+
+pub struct InstructionCacheInvalidateAllInnerShareable;
+pub struct BranchPredictorInvalidateAllInnerShareable;
+pub struct InstructionCacheInvalidateAll;
+pub struct InstructionCacheInvalidateByMva;
+pub struct BranchPredictorInvalidateAll;
+pub struct BranchPredictorInvalidateByMva;
+pub struct DataCacheInvalidateByMva;
+pub struct DataCacheInvalidateBySetWay;
+pub struct DataCacheCleanByMvaPoC;
+pub struct DataCacheCleanBySetWay;
+pub struct DataCacheCleanByMvaPoU;
+pub struct DataCacheCleanInvalidateByMva;
+pub struct DataCacheCleanInvalidateBySetWay;
+
+impl RegisterWriteOnly<u32, ()> for InstructionCacheInvalidateAllInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c1", "0", "0");
+}
+
+impl RegisterWriteOnly<u32, ()> for BranchPredictorInvalidateAllInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c1", "0", "6");
+}
+
+impl RegisterWriteOnly<u32, ()> for InstructionCacheInvalidateAll {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c5", "0", "0");
+}
+
+impl RegisterWriteOnly<u32, ()> for InstructionCacheInvalidateByMva {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c5", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for BranchPredictorInvalidateAll {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c5", "0", "6");
+}
+
+impl RegisterWriteOnly<u32, ()> for BranchPredictorInvalidateByMva {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c5", "0", "7");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheInvalidateByMva {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c6", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheInvalidateBySetWay {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c6", "0", "2");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheCleanByMvaPoC {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c10", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheCleanBySetWay {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c10", "0", "2");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheCleanByMvaPoU {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c11", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheCleanInvalidateByMva {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c14", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for DataCacheCleanInvalidateBySetWay {
+    sys_coproc_write_raw!(u32, "p15", "c7", "c14", "0", "2");
+}
+
+/// Public interface for the ICIALLUIS
+pub static ICIALLUIS: InstructionCacheInvalidateAllInnerShareable =
+    InstructionCacheInvalidateAllInnerShareable {};
+/// Public interface for the BPIALLIS
+pub static BPIALLIS: BranchPredictorInvalidateAllInnerShareable =
+    BranchPredictorInvalidateAllInnerShareable {};
+/// Public interface for the ICIALLU
+pub static ICIALLU: InstructionCacheInvalidateAll = InstructionCacheInvalidateAll {};
+/// Public interface for the ICIMVAU
+pub static ICIMVAU: InstructionCacheInvalidateByMva = InstructionCacheInvalidateByMva {};
+/// Public interface for the BPIALL
+pub static BPIALL: BranchPredictorInvalidateAll = BranchPredictorInvalidateAll {};
+/// Public interface for the BPIMVA
+pub static BPIMVA: BranchPredictorInvalidateByMva = BranchPredictorInvalidateByMva {};
+/// Public interface for the DCIMVAC
+pub static DCIMVAC: DataCacheInvalidateByMva = DataCacheInvalidateByMva {};
+/// Public interface for the DCISW
+pub static DCISW: DataCacheInvalidateBySetWay = DataCacheInvalidateBySetWay {};
+/// Public interface for the DCCMVAC
+pub static DCCMVAC: DataCacheCleanByMvaPoC = DataCacheCleanByMvaPoC {};
+/// Public interface for the DCCSW
+pub static DCCSW: DataCacheCleanBySetWay = DataCacheCleanBySetWay {};
+/// Public interface for the DCCMVAU
+pub static DCCMVAU: DataCacheCleanByMvaPoU = DataCacheCleanByMvaPoU {};
+/// Public interface for the DCCIMVAC
+pub static DCCIMVAC: DataCacheCleanInvalidateByMva = DataCacheCleanInvalidateByMva {};
+/// Public interface for the DCCISW
+pub static DCCISW: DataCacheCleanInvalidateBySetWay = DataCacheCleanInvalidateBySetWay {};
@@ -4,10 +4,11 @@ use register::cpu::RegisterReadWrite;
 use register::InMemoryRegister;
 use register::*;
 
-use crate::regs::program_state::PSR;
+use crate::regs::program_state::{PSR, SPSR};
 use crate::VirtualAddress;
 
 use crate::fmt;
+use core::mem;
 
 pub struct ProgramCounter;
 pub struct StackPointer;
@@ -30,6 +31,10 @@ impl<R: RegisterLongName> MemoryRegister<R> {
     pub const fn new(value: u32) -> Self {
         MemoryRegister(InMemoryRegister::new(value))
     }
+    /// Raw value of the register
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
 }
 impl<R: RegisterLongName> fmt::Debug for MemoryRegister<R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -59,6 +64,11 @@ pub struct CoreRegisters {
     pub psr: MemoryRegister<PSR::Register>, // 0x40
 }
 
+// The layout is used by assembly code saving and restoring the registers with stm and ldm, hence
+// it has to be exactly 17 consecutive words. These fail to compile if the size changes.
+const _: [(); 4] = [(); mem::size_of::<MemoryRegister<()>>()];
+const _: [(); 17 * 4] = [(); mem::size_of::<CoreRegisters>()];
+
 impl CoreRegisters {
     pub const fn new(psr: u32, sp: u32, pc: u32, lr: u32) -> Self {
         Self {
@@ -81,6 +91,31 @@ impl CoreRegisters {
             psr: MemoryRegister::new(psr),
         }
     }
+    /// The registers as words in the order of the struct
+    ///
+    /// The order r0 to r10, fp, ip, sp, lr, pc, psr is the same as in memory and is stable, so
+    /// it can be used as format for sending a register dump to a host.
+    pub fn as_words(&self) -> [u32; 17] {
+        [
+            self.r0.as_raw(),
+            self.r1.as_raw(),
+            self.r2.as_raw(),
+            self.r3.as_raw(),
+            self.r4.as_raw(),
+            self.r5.as_raw(),
+            self.r6.as_raw(),
+            self.r7.as_raw(),
+            self.r8.as_raw(),
+            self.r9.as_raw(),
+            self.r10.as_raw(),
+            self.fp.as_raw(),
+            self.ip.as_raw(),
+            self.sp.as_raw(),
+            self.lr.as_raw(),
+            self.pc.as_raw(),
+            self.psr.as_raw(),
+        ]
+    }
     pub fn set_pc(&mut self, value: VirtualAddress) {
         self.pc.0.set(value.as_u32());
     }
@@ -93,15 +128,101 @@ impl CoreRegisters {
     pub fn set_psr(&mut self, value: u32) {
         self.psr.0.set(value);
     }
+    /// The processor mode of the saved program state
+    ///
+    /// `None` indicates an invalid mode encoding, which is a sign of a corrupted program state.
+    /// Use `mode_raw` to report the value in that case.
     pub fn mode(&self) -> Option<PSR::MODE::Value> {
         //let psr_reg = InMemoryRegister::new(self.psr);
         self.psr.0.read_as_enum(PSR::MODE)
     }
+    /// The raw 5 bit mode field of the saved program state, including invalid encodings
+    pub fn mode_raw(&self) -> u8 {
+        self.psr.0.read(PSR::MODE) as u8
+    }
+    /// Store the SPSR of the current mode as program state
+    ///
+    /// Call this in an exception handler to save the program state of the interrupted code.
+    pub fn load_spsr(&mut self) {
+        self.set_psr(SPSR.get());
+    }
+    /// Write the program state to the SPSR of the current mode
+    ///
+    /// The SPSR has to be written before the exception return (`movs pc, lr` or `rfe`), since the
+    /// return copies the SPSR into the CPSR. Nothing may change the SPSR in between, in
+    /// particular no exception may be taken to the current mode.
+    ///
+    /// # Safety
+    /// The program state is used by the next exception return, hence it has to be a valid state
+    /// for the code returned to.
+    pub unsafe fn store_spsr(&self) {
+        SPSR.set(self.psr.0.get());
+    }
+    /// Return from the exception to the state in this struct
+    ///
+    /// Writes `psr` to the SPSR, restores r0 to r12 and branches to `pc` with `movs pc, lr`,
+    /// which copies the SPSR into the CPSR. The banked `sp` and `lr` of the target mode are not
+    /// restored.
+    ///
+    /// # Safety
+    /// Has to be called from an exception mode and `psr` and `pc` have to be a valid state to
+    /// return to.
+    pub unsafe fn exception_return(&self) -> ! {
+        llvm_asm!("
+            ldr r1, [r0, #0x40]
+            msr spsr_cxsf, r1
+            ldr lr, [r0, #0x3c]
+            ldm r0, {r0-r12}
+            movs pc, lr
+            " :: "{r0}"(self as *const Self) :: "volatile");
+        core::hint::unreachable_unchecked()
+    }
     //
 }
 
+impl Default for CoreRegisters {
+    /// All registers are zero and the processor is in USR mode
+    fn default() -> Self {
+        Self::new(PSR::MODE::USR.value, 0, 0, 0)
+    }
+}
+
 /// Program counter
 pub static PC: ProgramCounter = ProgramCounter {};
 
 /// Stack pointer
 pub static SP: StackPointer = StackPointer {};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn core_registers_offsets() {
+        let regs = CoreRegisters::new(0, 0, 0, 0);
+        let base = &regs as *const CoreRegisters as usize;
+        // Offset of every field against the layout used by the assembly code
+        let offsets = [
+            (&regs.r0 as *const _ as usize, 0x00),
+            (&regs.r1 as *const _ as usize, 0x04),
+            (&regs.r2 as *const _ as usize, 0x08),
+            (&regs.r3 as *const _ as usize, 0x0c),
+            (&regs.r4 as *const _ as usize, 0x10),
+            (&regs.r5 as *const _ as usize, 0x14),
+            (&regs.r6 as *const _ as usize, 0x18),
+            (&regs.r7 as *const _ as usize, 0x1c),
+            (&regs.r8 as *const _ as usize, 0x20),
+            (&regs.r9 as *const _ as usize, 0x24),
+            (&regs.r10 as *const _ as usize, 0x28),
+            (&regs.fp as *const _ as usize, 0x2c),
+            (&regs.ip as *const _ as usize, 0x30),
+            (&regs.sp as *const _ as usize, 0x34),
+            (&regs.lr as *const _ as usize, 0x38),
+            (&regs.pc as *const _ as usize, 0x3c),
+            (&regs.psr as *const _ as usize, 0x40),
+        ];
+        for (index, &(field, offset)) in offsets.iter().enumerate() {
+            assert_eq!(field - base, offset, "field {}", index);
+        }
+    }
+}
@@ -14,6 +14,12 @@ register_bitfields! {u32,
         WNR OFFSET(11) NUMBITS(1) [],
         EXT OFFSET(12) NUMBITS(1) [],
         CM OFFSET(13) NUMBITS(1) []
+    ],
+    pub IFS [
+        FS OFFSET(0) NUMBITS(4) [],
+        LPAE OFFSET(9) NUMBITS(1) [],
+        FS4 OFFSET(10) NUMBITS(1) [],
+        EXT OFFSET(12) NUMBITS(1) []
     ]
 }
 pub struct DataFaultAddress;
@@ -29,8 +35,33 @@ impl RegisterReadWrite<u32, DFS::Register> for DataFaultStatus {
     sys_coproc_read_raw!(u32, "p15", "c5", "c0", "0", "0");
     sys_coproc_write_raw!(u32, "p15", "c5", "c0", "0", "0");
 }
+impl RegisterReadWrite<u32, ()> for InstructionFaultAddress {
+    sys_coproc_read_raw!(u32, "p15", "c6", "c0", "0", "2");
+    sys_coproc_write_raw!(u32, "p15", "c6", "c0", "0", "2");
+}
+impl RegisterReadWrite<u32, IFS::Register> for InstructionFaultStatus {
+    sys_coproc_read_raw!(u32, "p15", "c5", "c0", "0", "1");
+    sys_coproc_write_raw!(u32, "p15", "c5", "c0", "0", "1");
+}
+
+impl DataFaultStatus {
+    /// Domain of the faulting access
+    ///
+    /// Only valid for domain and permission faults and for faults on section translations.
+    pub fn domain(&self) -> u8 {
+        self.read(DFS::DOMAIN) as u8
+    }
+    /// Fault status, the combination of the FS4 bit and the FS bits
+    pub fn fault_status(&self) -> u32 {
+        (self.read(DFS::FS4) << 4) | self.read(DFS::FS)
+    }
+}
 
 /// Public interface for the DFAR
 pub static DFAR: DataFaultAddress = DataFaultAddress {};
 /// Public interface for the DFAR
 pub static DFSR: DataFaultStatus = DataFaultStatus {};
+/// Public interface for the IFAR
+pub static IFAR: InstructionFaultAddress = InstructionFaultAddress {};
+/// Public interface for the IFSR
+pub static IFSR: InstructionFaultStatus = InstructionFaultStatus {};
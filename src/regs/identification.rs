@@ -0,0 +1,265 @@
+//! Register access to the identification registers
+//!
+//! Functional group according to the ARM ARM
+//!
+//! # Usage examples
+//! Select the level 1 data cache and read its size
+//! ```
+//!     CSSELR.write(CSSELR::LEVEL.val(0) + CSSELR::IND::Data);
+//!     CCSIDR.read(CCSIDR::NUMSETS)
+//! ```
+
+use core::fmt;
+pub use register::cpu::{RegisterReadOnly, RegisterReadWrite};
+use register::register_bitfields;
+
+register_bitfields! {u32,
+    pub CTR [
+        IMINLINE OFFSET(0) NUMBITS(4) [],
+        L1IP OFFSET(14) NUMBITS(2) [],
+        DMINLINE OFFSET(16) NUMBITS(4) [],
+        ERG OFFSET(20) NUMBITS(4) [],
+        CWG OFFSET(24) NUMBITS(4) [],
+        FORMAT OFFSET(29) NUMBITS(3) []
+    ],
+    pub CCSIDR [
+        LINESIZE OFFSET(0) NUMBITS(3) [],
+        ASSOCIATIVITY OFFSET(3) NUMBITS(10) [],
+        NUMSETS OFFSET(13) NUMBITS(15) [],
+        WA OFFSET(28) NUMBITS(1) [],
+        RA OFFSET(29) NUMBITS(1) [],
+        WB OFFSET(30) NUMBITS(1) [],
+        WT OFFSET(31) NUMBITS(1) []
+    ],
+    pub CLIDR [
+        CTYPE1 OFFSET(0) NUMBITS(3) [],
+        CTYPE2 OFFSET(3) NUMBITS(3) [],
+        CTYPE3 OFFSET(6) NUMBITS(3) [],
+        CTYPE4 OFFSET(9) NUMBITS(3) [],
+        CTYPE5 OFFSET(12) NUMBITS(3) [],
+        CTYPE6 OFFSET(15) NUMBITS(3) [],
+        CTYPE7 OFFSET(18) NUMBITS(3) [],
+        LOUIS OFFSET(21) NUMBITS(3) [],
+        LOC OFFSET(24) NUMBITS(3) [],
+        LOUU OFFSET(27) NUMBITS(3) []
+    ],
+    pub CSSELR [
+        IND OFFSET(0) NUMBITS(1) [Data = 0, Instruction = 1],
+        LEVEL OFFSET(1) NUMBITS(3) []
+    ],
+    pub MIDR [
+        REVISION OFFSET(0) NUMBITS(4) [],
+        PARTNUM OFFSET(4) NUMBITS(12) [],
+        ARCHITECTURE OFFSET(16) NUMBITS(4) [],
+        VARIANT OFFSET(20) NUMBITS(4) [],
+        IMPLEMENTER OFFSET(24) NUMBITS(8) []
+    ],
+    pub MPIDR [
+        AFF0 OFFSET(0) NUMBITS(8) [],
+        AFF1 OFFSET(8) NUMBITS(8) [],
+        AFF2 OFFSET(16) NUMBITS(8) [],
+        MT OFFSET(24) NUMBITS(1) [],
+        U OFFSET(30) NUMBITS(1) [],
+        M OFFSET(31) NUMBITS(1) []
+    ],
+    pub ID_PFR1 [
+        PROGMOD OFFSET(0) NUMBITS(4) [],
+        SECURITY OFFSET(4) NUMBITS(4) [],
+        MPROFMODEL OFFSET(8) NUMBITS(4) [],
+        VIRTUALIZATION OFFSET(12) NUMBITS(4) [],
+        GENTIMER OFFSET(16) NUMBITS(4) []
+    ]
+}
+
+pub struct CacheType;
+pub struct CacheSizeId;
+pub struct CacheLevelId;
+pub struct CacheSizeSelection;
+pub struct ProcessorFeature1;
+pub struct MainId;
+pub struct RevisionId;
+pub struct MultiprocessorAffinity;
+
+impl RegisterReadOnly<u32, CTR::Register> for CacheType {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "0", "1");
+}
+
+impl RegisterReadOnly<u32, CCSIDR::Register> for CacheSizeId {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "1", "0");
+}
+
+impl RegisterReadOnly<u32, CLIDR::Register> for CacheLevelId {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "1", "1");
+}
+
+impl RegisterReadWrite<u32, CSSELR::Register> for CacheSizeSelection {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "2", "0");
+    sys_coproc_write_raw!(u32, "p15", "c0", "c0", "2", "0");
+}
+
+impl RegisterReadOnly<u32, MIDR::Register> for MainId {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "0", "0");
+}
+
+impl RegisterReadOnly<u32, ()> for RevisionId {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "0", "6");
+}
+
+impl RegisterReadOnly<u32, MPIDR::Register> for MultiprocessorAffinity {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c0", "0", "5");
+}
+
+impl RegisterReadOnly<u32, ID_PFR1::Register> for ProcessorFeature1 {
+    sys_coproc_read_raw!(u32, "p15", "c0", "c1", "0", "1");
+}
+
+/// Public interface for the CTR
+pub static CTR: CacheType = CacheType {};
+/// Public interface for the CCSIDR
+pub static CCSIDR: CacheSizeId = CacheSizeId {};
+/// Public interface for the CLIDR
+pub static CLIDR: CacheLevelId = CacheLevelId {};
+/// Public interface for the CSSELR
+pub static CSSELR: CacheSizeSelection = CacheSizeSelection {};
+/// Public interface for the ID_PFR1
+pub static ID_PFR1: ProcessorFeature1 = ProcessorFeature1 {};
+
+/// Public interface for the MIDR
+pub static MIDR: MainId = MainId {};
+/// Public interface for the REVIDR
+pub static REVIDR: RevisionId = RevisionId {};
+/// Public interface for the MPIDR
+pub static MPIDR: MultiprocessorAffinity = MultiprocessorAffinity {};
+
+/// Returns whether the core implements the Security Extensions
+pub fn has_security_extensions() -> bool {
+    ID_PFR1.read(ID_PFR1::SECURITY) != 0
+}
+
+/// Returns whether the core implements the Virtualization Extensions
+pub fn has_virtualization_extensions() -> bool {
+    ID_PFR1.read(ID_PFR1::VIRTUALIZATION) != 0
+}
+
+/// Returns whether the core implements the Multiprocessing Extensions
+///
+/// The Multiprocessing Extensions add the broadcast and the all ASID TLB and cache maintenance
+/// operations like `TLBIMVAA`, which are undefined instructions on other cores, e.g. the
+/// Cortex-A8. Implementations with the extensions read MPIDR.M as one.
+pub fn has_multiprocessing_extensions() -> bool {
+    MPIDR.is_set(MPIDR::M)
+}
+
+/// Cores designed by ARM, decoded from the part number in the MIDR
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PartNumber {
+    CortexA5,
+    CortexA7,
+    CortexA8,
+    CortexA9,
+    CortexA12,
+    CortexA15,
+    CortexA17,
+    CortexR4,
+    CortexR5,
+    CortexR7,
+    CortexR8,
+    /// Any other part number, or a core not designed by ARM
+    Other(u16),
+}
+
+impl PartNumber {
+    /// Decode the part number of a core with the given implementer
+    pub fn new(implementer: u8, part: u16) -> Self {
+        if implementer != IMPLEMENTER_ARM {
+            return PartNumber::Other(part);
+        }
+        match part {
+            0xc05 => PartNumber::CortexA5,
+            0xc07 => PartNumber::CortexA7,
+            0xc08 => PartNumber::CortexA8,
+            0xc09 => PartNumber::CortexA9,
+            0xc0d => PartNumber::CortexA12,
+            0xc0f => PartNumber::CortexA15,
+            0xc0e => PartNumber::CortexA17,
+            0xc14 => PartNumber::CortexR4,
+            0xc15 => PartNumber::CortexR5,
+            0xc17 => PartNumber::CortexR7,
+            0xc18 => PartNumber::CortexR8,
+            _ => PartNumber::Other(part),
+        }
+    }
+}
+
+impl fmt::Display for PartNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            PartNumber::CortexA5 => "Cortex-A5",
+            PartNumber::CortexA7 => "Cortex-A7",
+            PartNumber::CortexA8 => "Cortex-A8",
+            PartNumber::CortexA9 => "Cortex-A9",
+            PartNumber::CortexA12 => "Cortex-A12",
+            PartNumber::CortexA15 => "Cortex-A15",
+            PartNumber::CortexA17 => "Cortex-A17",
+            PartNumber::CortexR4 => "Cortex-R4",
+            PartNumber::CortexR5 => "Cortex-R5",
+            PartNumber::CortexR7 => "Cortex-R7",
+            PartNumber::CortexR8 => "Cortex-R8",
+            PartNumber::Other(part) => return write!(f, "part {:#05x}", part),
+        };
+        f.write_str(string)
+    }
+}
+
+/// Implementer code of ARM Limited in the MIDR
+pub const IMPLEMENTER_ARM: u8 = 0x41;
+
+/// Identification of the core, combined from the MIDR, REVIDR and MPIDR
+#[derive(Copy, Clone, Debug)]
+pub struct CpuInfo {
+    pub implementer: u8,
+    pub part: PartNumber,
+    /// Major revision, the `r` in `r2p10`
+    pub variant: u8,
+    /// Minor revision, the `p` in `r2p10`
+    pub revision: u8,
+    /// Implementation defined revision information from the REVIDR
+    pub revidr: u32,
+    /// Affinity levels 0 to 2 from the MPIDR
+    pub affinity: [u8; 3],
+}
+
+/// Read the identification registers of the current core
+pub fn cpu_info() -> CpuInfo {
+    let midr = MIDR.extract();
+    let mpidr = MPIDR.extract();
+    let implementer = midr.read(MIDR::IMPLEMENTER) as u8;
+    CpuInfo {
+        implementer,
+        part: PartNumber::new(implementer, midr.read(MIDR::PARTNUM) as u16),
+        variant: midr.read(MIDR::VARIANT) as u8,
+        revision: midr.read(MIDR::REVISION) as u8,
+        revidr: REVIDR.get(),
+        affinity: [
+            mpidr.read(MPIDR::AFF0) as u8,
+            mpidr.read(MPIDR::AFF1) as u8,
+            mpidr.read(MPIDR::AFF2) as u8,
+        ],
+    }
+}
+
+impl fmt::Display for CpuInfo {
+    /// Formats the info as e.g. `ARM Cortex-A9 r2p10 (core 0)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.implementer == IMPLEMENTER_ARM {
+            f.write_str("ARM ")?;
+        } else {
+            write!(f, "implementer {:#04x} ", self.implementer)?;
+        }
+        write!(
+            f,
+            "{} r{}p{} (core {})",
+            self.part, self.variant, self.revision, self.affinity[0]
+        )
+    }
+}
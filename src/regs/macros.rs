@@ -15,7 +15,10 @@ macro_rules! __read_raw {
                     reg
                 }
 
-                #[cfg(not(target_arch = "arm"))]
+                #[cfg(all(not(target_arch = "arm"), feature = "mock"))]
+                () => $crate::regs::mock::read($crate::regs::mock::key($asm_reg_name)),
+
+                #[cfg(all(not(target_arch = "arm"), not(feature = "mock")))]
                 () => unimplemented!(),
             }
         }
@@ -25,7 +28,7 @@ macro_rules! __read_raw {
 macro_rules! __write_raw {
     ($width:ty, $asm_instr:tt, $asm_reg_name:tt) => {
         /// Writes raw bits to the CPU register.
-        #[cfg_attr(not(target_arch = "arm"), allow(unused_variables))]
+        #[cfg_attr(all(not(target_arch = "arm"), not(feature = "mock")), allow(unused_variables))]
         #[inline]
         fn set(&self, value: $width) {
             match () {
@@ -36,7 +39,10 @@ macro_rules! __write_raw {
                     }
                 }
 
-                #[cfg(not(target_arch = "arm"))]
+                #[cfg(all(not(target_arch = "arm"), feature = "mock"))]
+                () => $crate::regs::mock::write($crate::regs::mock::key($asm_reg_name), value),
+
+                #[cfg(all(not(target_arch = "arm"), not(feature = "mock")))]
                 () => unimplemented!(),
             }
         }
@@ -61,7 +67,10 @@ macro_rules! sys_coproc_read_raw {
                     reg
                 }
 
-                #[cfg(not(target_arch = "arm"))]
+                #[cfg(all(not(target_arch = "arm"), feature = "mock"))]
+                () => $crate::regs::mock::read($crate::regs::mock::key(concat!($asm_cp, $asm_crn, $asm_crm, $asm_opc1, $asm_opc2))),
+
+                #[cfg(all(not(target_arch = "arm"), not(feature = "mock")))]
                 () => unimplemented!(),
             }
         }
@@ -73,7 +82,7 @@ macro_rules! sys_coproc_read_raw {
 macro_rules! sys_coproc_write_raw {
     ($width:ty, $asm_cp:tt, $asm_crn:tt, $asm_crm:tt, $asm_opc1:tt, $asm_opc2:tt) => {
         /// Writes raw bits to the CPU register.
-        #[cfg_attr(not(target_arch = "arm"), allow(unused_variables))]
+        #[cfg_attr(all(not(target_arch = "arm"), not(feature = "mock")), allow(unused_variables))]
         #[inline]
         fn set(&self, value: $width) {
             match () {
@@ -84,7 +93,10 @@ macro_rules! sys_coproc_write_raw {
                     }
                 }
 
-                #[cfg(not(target_arch = "arm"))]
+                #[cfg(all(not(target_arch = "arm"), feature = "mock"))]
+                () => $crate::regs::mock::write($crate::regs::mock::key(concat!($asm_cp, $asm_crn, $asm_crm, $asm_opc1, $asm_opc2)), value),
+
+                #[cfg(all(not(target_arch = "arm"), not(feature = "mock")))]
                 () => unimplemented!(),
             }
         }
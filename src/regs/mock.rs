@@ -0,0 +1,79 @@
+//! Simulated register storage for host builds
+//!
+//! With the feature `mock` the register accessors on targets other than arm read and write the
+//! values stored here instead of panicking. Every register has its own value, which starts as
+//! zero. The storage is shared by all threads, hence tests touching the same register have to be
+//! serialized.
+//!
+//! # Usage examples
+//! Pretend that the MMU is enabled
+//! ```
+//!     SCTLR.modify(SCTLR::MMU::Enable);
+//!     assert!(SCTLR.is_set(SCTLR::MMU));
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+const SLOTS: usize = 128;
+
+struct Slot {
+    key: AtomicU32,
+    value: AtomicU32,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: Slot = Slot {
+    key: AtomicU32::new(0),
+    value: AtomicU32::new(0),
+};
+
+static STORAGE: [Slot; SLOTS] = [EMPTY; SLOTS];
+
+/// Key identifying a register, computed from its assembly name
+///
+/// The key is never zero, which marks an unused slot.
+pub const fn key(name: &str) -> u32 {
+    // FNV-1a
+    let bytes = name.as_bytes();
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    hash | 1
+}
+
+fn slot(key: u32) -> &'static Slot {
+    let start = key as usize % SLOTS;
+    for i in 0..SLOTS {
+        let slot = &STORAGE[(start + i) % SLOTS];
+        match slot
+            .key
+            .compare_exchange(0, key, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => return slot,
+            Err(current) if current == key => return slot,
+            Err(_) => {}
+        }
+    }
+    panic!("too many mocked registers");
+}
+
+/// Read the simulated value of a register
+pub fn read(key: u32) -> u32 {
+    slot(key).value.load(Ordering::SeqCst)
+}
+
+/// Write the simulated value of a register
+pub fn write(key: u32, value: u32) {
+    slot(key).value.store(value, Ordering::SeqCst)
+}
+
+/// Reset all simulated registers to zero
+pub fn reset() {
+    for slot in STORAGE.iter() {
+        slot.value.store(0, Ordering::SeqCst);
+    }
+}
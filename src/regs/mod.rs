@@ -7,10 +7,154 @@
 mod macros;
 
 pub mod address_translation;
+pub mod cache_maintenance;
 pub mod core_regs; // this is called core_regs to avoid a name clash with the core crate
 pub mod fault_handling;
+pub mod identification;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod program_state;
 pub mod security;
+pub mod tlb_lockdown;
+pub mod tlb_maintenance;
 pub mod vmem_control;
 
 pub use register::cpu::*;
+
+use core::sync::atomic::{compiler_fence, Ordering};
+use program_state::{CPSR, PSR};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Marker for registers whose effect is local to the executing core
+///
+/// These registers are either part of the core itself or banked per core, so the statics only
+/// ever access the registers of the core that is executing the code. Changes to a register are not
+/// visible to the other cores and concurrent access from the same core (e.g. by an interrupt
+/// handler) has to be synchronized with `with_local`.
+///
+/// With the Multiprocessing Extensions the cache maintenance operations by MVA on shareable memory
+/// also reach the caches of the other cores in the shareability domain. They are still issued
+/// through the registers of the executing core, which is all that `with_local` protects.
+pub trait CpuLocal: private::Sealed {}
+
+/// Marker for maintenance operations that are broadcast to the Inner Shareable domain
+///
+/// These operations affect the caches and TLBs of all cores in the domain, hence they are not
+/// `CpuLocal` and masking interrupts on the current core does not order them against the other
+/// cores. Completion on all cores is only guaranteed after a `dsb`.
+pub trait Broadcast: private::Sealed {}
+
+macro_rules! cpu_local {
+    ($($reg:ty),* $(,)?) => {
+        $(
+            impl private::Sealed for $reg {}
+            impl CpuLocal for $reg {}
+        )*
+    };
+}
+
+macro_rules! broadcast {
+    ($($reg:ty),* $(,)?) => {
+        $(
+            impl private::Sealed for $reg {}
+            impl Broadcast for $reg {}
+        )*
+    };
+}
+
+cpu_local!(
+    address_translation::Stage1CurrentStatePL1Read,
+    address_translation::Stage1CurrentStatePL1Write,
+    address_translation::Stage1CurrentStateUnpriviledgedRead,
+    address_translation::Stage1CurrentStateUnpriviledgedWrite,
+    address_translation::PhysicalAddress,
+    cache_maintenance::InstructionCacheInvalidateAll,
+    cache_maintenance::InstructionCacheInvalidateByMva,
+    cache_maintenance::BranchPredictorInvalidateAll,
+    cache_maintenance::BranchPredictorInvalidateByMva,
+    cache_maintenance::DataCacheInvalidateByMva,
+    cache_maintenance::DataCacheInvalidateBySetWay,
+    cache_maintenance::DataCacheCleanByMvaPoC,
+    cache_maintenance::DataCacheCleanBySetWay,
+    cache_maintenance::DataCacheCleanByMvaPoU,
+    cache_maintenance::DataCacheCleanInvalidateByMva,
+    cache_maintenance::DataCacheCleanInvalidateBySetWay,
+    core_regs::ProgramCounter,
+    core_regs::StackPointer,
+    fault_handling::DataFaultAddress,
+    fault_handling::DataFaultStatus,
+    fault_handling::InstructionFaultAddress,
+    fault_handling::InstructionFaultStatus,
+    identification::CacheType,
+    identification::CacheSizeId,
+    identification::CacheLevelId,
+    identification::CacheSizeSelection,
+    identification::ProcessorFeature1,
+    identification::MainId,
+    identification::RevisionId,
+    identification::MultiprocessorAffinity,
+    program_state::CurrentProgramState,
+    program_state::SavedProgramState,
+    security::InterruptStatus,
+    security::MonitorVectorBaseAddress,
+    security::NonSecureAccessControl,
+    security::SecureConfiguration,
+    security::SecureDebugEnable,
+    security::VectorBaseAddress,
+    tlb_lockdown::DataTlbLockdown,
+    tlb_lockdown::InstructionTlbLockdown,
+    tlb_maintenance::InvalidateAll,
+    tlb_maintenance::InvalidateByMva,
+    tlb_maintenance::InvalidateByAsid,
+    tlb_maintenance::InvalidateByMvaAllAsid,
+    vmem_control::SystemControl,
+    vmem_control::TranslationTableBase0,
+    vmem_control::TranslationTableBase1,
+    vmem_control::TranslationTableBaseControl,
+    vmem_control::ContextId,
+    vmem_control::DomainAccessControl,
+);
+
+broadcast!(
+    cache_maintenance::InstructionCacheInvalidateAllInnerShareable,
+    cache_maintenance::BranchPredictorInvalidateAllInnerShareable,
+    tlb_maintenance::InvalidateAllInnerShareable,
+    tlb_maintenance::InvalidateByMvaInnerShareable,
+    tlb_maintenance::InvalidateByAsidInnerShareable,
+    tlb_maintenance::InvalidateByMvaAllAsidInnerShareable,
+);
+
+/// Run a closure on a `CpuLocal` register with IRQs and FIQs masked on the current core
+///
+/// The closure gets the register `reg`, which restricts the critical section to registers whose
+/// effect is local to the core. The previous masking state is restored afterwards, so calls can be
+/// nested. Use this for read-modify-write sequences that an interrupt handler might also touch,
+/// e.g.
+///
+/// ```
+///     with_local(&SCTLR, |sctlr| sctlr.modify(SCTLR::BRANCHPRED::Enable));
+/// ```
+///
+/// If IRQs and FIQs are already masked, e.g. during early boot or in nested calls, the CPSR is not
+/// written and the section reduces to a compiler fence around the closure. The check reads the
+/// CPSR each time, so the masking is never skipped once interrupts are enabled.
+pub fn with_local<T, F, R>(reg: &T, f: F) -> R
+where
+    T: CpuLocal,
+    F: FnOnce(&T) -> R,
+{
+    let saved = CPSR.extract();
+    if saved.is_set(PSR::IRQ) && saved.is_set(PSR::FIQ) {
+        compiler_fence(Ordering::SeqCst);
+        let out = f(reg);
+        compiler_fence(Ordering::SeqCst);
+        return out;
+    }
+    CPSR.modify(PSR::IRQ::Masked + PSR::FIQ::Masked);
+    let out = f(reg);
+    CPSR.modify(PSR::IRQ.val(saved.read(PSR::IRQ)) + PSR::FIQ.val(saved.read(PSR::FIQ)));
+    out
+}
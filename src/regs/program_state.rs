@@ -14,6 +14,11 @@
 //!     CPSR.modify(PSR::FIQ::NotMasked);
 //! ```
 
+use crate::asm;
+use crate::regs::identification::has_virtualization_extensions;
+use crate::structures::interrupts::ExceptionKind;
+use crate::structures::paging::{PageError, Result};
+use crate::VirtualAddress;
 use core::fmt;
 use register::{cpu::RegisterReadWrite, register_bitfields, FieldValue};
 
@@ -36,7 +41,8 @@ register_bitfields! {u32,
         IRQ OFFSET(7) NUMBITS(1) [Masked = 1, NotMasked = 0],
         ABT OFFSET(8) NUMBITS(1) [Masked = 1, NotMasked = 0],
         ENDIAN OFFSET(9) NUMBITS(1) [Little = 0b0, Big = 0b1],
-        JAZELLE OFFSET(24) NUMBITS(1) []
+        JAZELLE OFFSET(24) NUMBITS(1) [],
+        Q OFFSET(27) NUMBITS(1) []
     ]
 }
 
@@ -61,10 +67,93 @@ impl fmt::Display for PSR::MODE::Value {
     }
 }
 
+/// The current processor mode
+///
+/// All architectural modes are covered by the enum, hence `None` indicates an invalid mode
+/// encoding. Use `current_mode_raw` to report the value in that case.
 pub fn get_current_mode() -> Option<PSR::MODE::Value> {
     CPSR.read_as_enum(PSR::MODE)
 }
 
+/// The raw 5 bit mode field of the CPSR, including invalid encodings
+pub fn current_mode_raw() -> u8 {
+    CPSR.read(PSR::MODE) as u8
+}
+
+/// Returns whether a saturating instruction has saturated since the Q flag was cleared
+pub fn saturation_occurred() -> bool {
+    CPSR.is_set(PSR::Q)
+}
+
+/// Clear the sticky saturation flag
+pub fn clear_saturation() {
+    CPSR.modify(PSR::Q::CLEAR);
+    asm::isb();
+}
+
+/// Set the stack pointer of another processor mode
+///
+/// Switches to the given mode, sets the banked stack pointer and switches back to the current
+/// mode. Everything happens in a single assembly block, hence the stack of the caller is not
+/// touched while the processor is in the other mode. IRQs and FIQs are masked while in the other
+/// mode, so that no exception runs on a half set up stack. The operands are kept in r0 to r3,
+/// because r8 to r12 are banked in `FIQ` mode.
+///
+/// The mode is read back after the switch and the stack pointer is only written if the switch
+/// succeeded, otherwise `PageError::ModeError` is returned. The stack of `HYP` cannot be set by
+/// switching modes, it is written with the banked `msr SP_hyp` instead.
+///
+/// # Safety
+/// `stack_top` has to point to the top of a valid stack for the given mode. Furthermore
+/// * the function has to be called from a privileged mode, `USR` and the current mode are rejected
+///   with `PageError::ModeError`, setting the stack of `SYS` also sets the stack of `USR`,
+/// * `MON` can only be entered from the Secure state, from the Non-secure state the mode change is
+///   illegal and `PageError::ModeError` is returned,
+/// * `HYP` is only supported from `MON` on cores with the Virtualization Extensions, all other
+///   cases are rejected with `PageError::ModeError`.
+pub unsafe fn set_mode_stack(mode: PSR::MODE::Value, stack_top: VirtualAddress) -> Result<()> {
+    let mode = mode as u32;
+    let current = current_mode_raw() as u32;
+    if mode == PSR::MODE::Value::USR as u32
+        || current == PSR::MODE::Value::USR as u32
+        || mode == current
+    {
+        return Err(PageError::ModeError);
+    }
+    if mode == PSR::MODE::Value::HYP as u32 {
+        if current != PSR::MODE::Value::MON as u32 || !has_virtualization_extensions() {
+            return Err(PageError::ModeError);
+        }
+        llvm_asm!(".arch_extension virt
+                   msr SP_hyp, $0"
+                  :: "r"(stack_top.as_u32())
+                  : "memory"
+                  : "volatile");
+        return Ok(());
+    }
+    let _saved: u32;
+    let entered: u32;
+    llvm_asm!("mrs $0, cpsr
+               bic $1, $0, #0x1f
+               orr $1, $1, $2
+               orr $1, $1, #0xc0
+               msr cpsr_c, $1
+               mrs $1, cpsr
+               and $1, $1, #0x1f
+               cmp $1, $2
+               moveq sp, $3
+               msr cpsr_c, $0"
+              : "={r0}"(_saved), "={r1}"(entered)
+              : "{r2}"(mode), "{r3}"(stack_top.as_u32())
+              : "cc", "memory"
+              : "volatile");
+    if entered == mode {
+        Ok(())
+    } else {
+        Err(PageError::ModeError)
+    }
+}
+
 pub struct CurrentProgramState;
 
 impl RegisterReadWrite<u32, PSR::Register> for CurrentProgramState {
@@ -79,6 +168,69 @@ impl RegisterReadWrite<u32, PSR::Register> for SavedProgramState {
     psr_write_raw!(u32, "spsr");
 }
 
+/// Registers that have a separate copy in some modes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BankedReg {
+    R8,
+    R9,
+    R10,
+    R11,
+    R12,
+    Sp,
+    Lr,
+    /// Exception link register, only banked in HYP mode
+    ElrHyp,
+    Spsr,
+}
+
+/// Characteristics of a processor mode
+#[derive(Copy, Clone, Debug)]
+pub struct ModeInfo {
+    /// The mode has a saved program state register
+    pub has_spsr: bool,
+    /// The mode can access system registers and change the mode
+    pub privileged: bool,
+    /// Registers that are banked in this mode
+    pub banked_regs: &'static [BankedReg],
+    /// Exceptions that are taken to this mode
+    pub exceptions: &'static [ExceptionKind],
+}
+
+impl ModeInfo {
+    /// Look up the characteristics of a mode
+    ///
+    /// Use `exception_return_offset` with the entries of `exceptions` for the LR adjustment.
+    pub fn of(mode: PSR::MODE::Value) -> Self {
+        use BankedReg::*;
+        const EXCEPTION_REGS: &[BankedReg] = &[Sp, Lr, Spsr];
+        let privileged = !matches!(mode, PSR::MODE::Value::USR);
+        let (has_spsr, banked_regs, exceptions): (_, &[_], &[_]) = match mode {
+            PSR::MODE::Value::USR | PSR::MODE::Value::SYS => (false, &[], &[]),
+            PSR::MODE::Value::FIQ => (
+                true,
+                &[R8, R9, R10, R11, R12, Sp, Lr, Spsr],
+                &[ExceptionKind::Fiq],
+            ),
+            PSR::MODE::Value::IRQ => (true, EXCEPTION_REGS, &[ExceptionKind::Irq]),
+            PSR::MODE::Value::SVC => (true, EXCEPTION_REGS, &[ExceptionKind::SupervisorCall]),
+            PSR::MODE::Value::MON => (true, EXCEPTION_REGS, &[ExceptionKind::SecureMonitorCall]),
+            PSR::MODE::Value::ABT => (
+                true,
+                EXCEPTION_REGS,
+                &[ExceptionKind::PrefetchAbort, ExceptionKind::DataAbort],
+            ),
+            PSR::MODE::Value::HYP => (true, &[Sp, ElrHyp, Spsr], &[]),
+            PSR::MODE::Value::UND => (true, EXCEPTION_REGS, &[ExceptionKind::Undefined]),
+        };
+        ModeInfo {
+            has_spsr,
+            privileged,
+            banked_regs,
+            exceptions,
+        }
+    }
+}
+
 /// Current Program State register
 pub static CPSR: CurrentProgramState = CurrentProgramState {};
 /// Saved Program State register
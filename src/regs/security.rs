@@ -3,6 +3,22 @@
 //! Functional group according to the ARM ARM
 
 use register::cpu::*;
+use register::register_bitfields;
+
+register_bitfields! {u32,
+    pub ISR [
+        F OFFSET(6) NUMBITS(1) [],
+        I OFFSET(7) NUMBITS(1) [],
+        A OFFSET(8) NUMBITS(1) []
+    ],
+    pub NSACR [
+        CP10 OFFSET(10) NUMBITS(1) [SecureOnly = 0, NonSecure = 1],
+        CP11 OFFSET(11) NUMBITS(1) [SecureOnly = 0, NonSecure = 1],
+        NSD32DIS OFFSET(14) NUMBITS(1) [],
+        NSASEDIS OFFSET(15) NUMBITS(1) [],
+        NS_SMP OFFSET(18) NUMBITS(1) []
+    ]
+}
 
 pub struct InterruptStatus;
 pub struct MonitorVectorBaseAddress;
@@ -11,7 +27,7 @@ pub struct SecureConfiguration;
 pub struct SecureDebugEnable;
 pub struct VectorBaseAddress;
 
-impl RegisterReadOnly<u32, ()> for InterruptStatus {
+impl RegisterReadOnly<u32, ISR::Register> for InterruptStatus {
     sys_coproc_read_raw!(u32, "p15", "c12", "c1", "0", "0");
 }
 
@@ -20,7 +36,7 @@ impl RegisterReadWrite<u32, ()> for MonitorVectorBaseAddress {
     sys_coproc_write_raw!(u32, "p15", "c12", "c0", "0", "1");
 }
 
-impl RegisterReadWrite<u32, ()> for NonSecureAccessControl {
+impl RegisterReadWrite<u32, NSACR::Register> for NonSecureAccessControl {
     sys_coproc_read_raw!(u32, "p15", "c1", "c1", "0", "2");
     sys_coproc_write_raw!(u32, "p15", "c1", "c1", "0", "2");
 }
@@ -46,3 +62,49 @@ pub static NSACR: NonSecureAccessControl = NonSecureAccessControl {};
 pub static SCR: SecureConfiguration = SecureConfiguration {};
 pub static SDER: SecureDebugEnable = SecureDebugEnable {};
 pub static VBAR: VectorBaseAddress = VectorBaseAddress {};
+
+/// Returns whether an IRQ is pending
+pub fn pending_irq() -> bool {
+    ISR.is_set(ISR::I)
+}
+
+/// Returns whether an FIQ is pending
+pub fn pending_fiq() -> bool {
+    ISR.is_set(ISR::F)
+}
+
+/// Returns whether an external abort is pending
+pub fn pending_abort() -> bool {
+    ISR.is_set(ISR::A)
+}
+
+/// Allow the non-secure world to access the floating point unit (CP10 and CP11)
+///
+/// Only has an effect if executed in the secure world.
+pub fn allow_nonsecure_fp() {
+    NSACR.modify(NSACR::CP10::NonSecure + NSACR::CP11::NonSecure);
+}
+
+/// Issue a Secure Monitor Call
+///
+/// The function identifier is passed in r0 and `args` in r1 to r3, following the SMC Calling
+/// Convention used by PSCI and other firmware services. Returns r0 to r3 after the call. The
+/// calling convention preserves r4 to r14, hence only the flags and memory are clobbered.
+///
+/// # Safety
+/// Must be executed at PL1 or higher, otherwise the instruction is undefined. The effects of the
+/// call are defined by the secure firmware, e.g. PSCI `CPU_ON` starts another core at the given
+/// entry point.
+pub unsafe fn smc(function: u32, args: [u32; 3]) -> [u32; 4] {
+    let r0: u32;
+    let r1: u32;
+    let r2: u32;
+    let r3: u32;
+    llvm_asm!(".arch_extension sec
+               smc #0"
+              : "={r0}"(r0), "={r1}"(r1), "={r2}"(r2), "={r3}"(r3)
+              : "{r0}"(function), "{r1}"(args[0]), "{r2}"(args[1]), "{r3}"(args[2])
+              : "cc", "memory"
+              : "volatile");
+    [r0, r1, r2, r3]
+}
@@ -0,0 +1,78 @@
+//! Register access to the TLB lockdown registers
+//!
+//! TLB lockdown is implementation defined and only available on some parts, e.g. the Cortex-A8.
+//! The register layout used here is the one of the Cortex-A8, check the technical reference manual
+//! of the target part before using this module. On parts without TLB lockdown the accesses are
+//! undefined instructions.
+//!
+//! # Usage examples
+//! Lock the translation of a hot code path into the data TLB
+//! ```
+//!     unsafe { lock_translation(VirtualAddress::from_ptr(&HOT_DATA))? };
+//! ```
+
+use crate::asm;
+use crate::regs::tlb_maintenance::{RegisterWriteOnly, TLBIMVA};
+use crate::regs::vmem_control::{CONTEXTIDR, CONTEXTIDR::ASID};
+use crate::structures::paging::{PageError, Result};
+use crate::VirtualAddress;
+use core::ptr;
+pub use register::cpu::RegisterReadWrite;
+use register::register_bitfields;
+
+register_bitfields! {u32,
+    pub TLBLR [
+        /// Entries loaded while set are preserved from replacement
+        P OFFSET(0) NUMBITS(1) [],
+        /// Entry that is replaced by the next table walk
+        VICTIM OFFSET(22) NUMBITS(5) [],
+        /// Lowest entry that is not locked
+        BASE OFFSET(27) NUMBITS(5) []
+    ]
+}
+
+pub struct DataTlbLockdown;
+pub struct InstructionTlbLockdown;
+
+impl RegisterReadWrite<u32, TLBLR::Register> for DataTlbLockdown {
+    sys_coproc_read_raw!(u32, "p15", "c10", "c0", "0", "0");
+    sys_coproc_write_raw!(u32, "p15", "c10", "c0", "0", "0");
+}
+
+impl RegisterReadWrite<u32, TLBLR::Register> for InstructionTlbLockdown {
+    sys_coproc_read_raw!(u32, "p15", "c10", "c0", "0", "1");
+    sys_coproc_write_raw!(u32, "p15", "c10", "c0", "0", "1");
+}
+
+/// Public interface for the data TLB lockdown register
+pub static DTLBLR: DataTlbLockdown = DataTlbLockdown {};
+/// Public interface for the instruction TLB lockdown register
+pub static ITLBLR: InstructionTlbLockdown = InstructionTlbLockdown {};
+
+/// Load the translation of `virt` into the data TLB and lock it
+///
+/// The entry for the current ASID is invalidated, the P bit is set and `virt` is read, so that
+/// the table walk loads the translation into the first unlocked entry. Afterwards the base is
+/// incremented past the locked entry. Returns `PageError::SizeError` if all lockdown entries are
+/// in use.
+///
+/// # Safety
+/// TLB lockdown is implementation defined, see the module documentation. `virt` has to be mapped
+/// and readable and the function must not be interrupted by code using the TLB lockdown.
+pub unsafe fn lock_translation(virt: VirtualAddress) -> Result<()> {
+    let base = DTLBLR.read(TLBLR::BASE);
+    if base == TLBLR::BASE.mask {
+        return Err(PageError::SizeError);
+    }
+    DTLBLR.write(TLBLR::P::SET + TLBLR::VICTIM.val(base) + TLBLR::BASE.val(base));
+    // Non-global entries are tagged with the current ASID. TLBIMVAA is not available on parts
+    // without the Multiprocessing Extensions like the Cortex-A8.
+    TLBIMVA.set((virt.as_u32() & !0xfff) | CONTEXTIDR.read(ASID));
+    asm::dsb();
+    asm::isb();
+    ptr::read_volatile(virt.as_ptr::<u8>());
+    asm::dsb();
+    DTLBLR.write(TLBLR::VICTIM.val(base + 1) + TLBLR::BASE.val(base + 1));
+    asm::isb();
+    Ok(())
+}
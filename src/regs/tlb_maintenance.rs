@@ -0,0 +1,74 @@
+//! Register access to the TLB maintenance operations
+//!
+//! Functional group according to the ARM ARM
+//!
+//! # Usage examples
+//! Invalidate the whole unified TLB
+//! ```
+//!     TLBIALL.set(0);
+//! ```
+//! Invalidate the entries of a single page
+//! ```
+//!     TLBIMVAA.set(virt_addr.as_u32());
+//! ```
+
+pub use register::cpu::RegisterWriteOnly;
+
+pub struct InvalidateAllInnerShareable;
+pub struct InvalidateByMvaInnerShareable;
+pub struct InvalidateByAsidInnerShareable;
+pub struct InvalidateByMvaAllAsidInnerShareable;
+pub struct InvalidateAll;
+pub struct InvalidateByMva;
+pub struct InvalidateByAsid;
+pub struct InvalidateByMvaAllAsid;
+
+impl RegisterWriteOnly<u32, ()> for InvalidateAllInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c3", "0", "0");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByMvaInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c3", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByAsidInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c3", "0", "2");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByMvaAllAsidInnerShareable {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c3", "0", "3");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateAll {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c7", "0", "0");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByMva {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c7", "0", "1");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByAsid {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c7", "0", "2");
+}
+
+impl RegisterWriteOnly<u32, ()> for InvalidateByMvaAllAsid {
+    sys_coproc_write_raw!(u32, "p15", "c8", "c7", "0", "3");
+}
+
+/// Public interface for the TLBIALLIS
+pub static TLBIALLIS: InvalidateAllInnerShareable = InvalidateAllInnerShareable {};
+/// Public interface for the TLBIMVAIS
+pub static TLBIMVAIS: InvalidateByMvaInnerShareable = InvalidateByMvaInnerShareable {};
+/// Public interface for the TLBIASIDIS
+pub static TLBIASIDIS: InvalidateByAsidInnerShareable = InvalidateByAsidInnerShareable {};
+/// Public interface for the TLBIMVAAIS
+pub static TLBIMVAAIS: InvalidateByMvaAllAsidInnerShareable =
+    InvalidateByMvaAllAsidInnerShareable {};
+/// Public interface for the TLBIALL
+pub static TLBIALL: InvalidateAll = InvalidateAll {};
+/// Public interface for the TLBIMVA
+pub static TLBIMVA: InvalidateByMva = InvalidateByMva {};
+/// Public interface for the TLBIASID
+pub static TLBIASID: InvalidateByAsid = InvalidateByAsid {};
+/// Public interface for the TLBIMVAA
+pub static TLBIMVAA: InvalidateByMvaAllAsid = InvalidateByMvaAllAsid {};
@@ -11,9 +11,14 @@
 //! ```
 //! Enable the MMU
 //! ```
-//!     SCTLR.modify(SCTLR::MMU::Enable);
+//!     enable_mmu();
 //! ```
 
+use crate::asm;
+use crate::regs::cache_maintenance::*;
+use crate::regs::tlb_maintenance::*;
+use crate::structures::cache::clean_invalidate_dcache_all;
+use crate::structures::paging::{PageError, Result};
 pub use register::cpu::RegisterReadWrite;
 use register::register_bitfields;
 
@@ -21,6 +26,8 @@ register_bitfields! {u32,
     pub SCTLR [
         MMU OFFSET(0) NUMBITS(1) [Enable = 1, Disable = 0],
         CACHE OFFSET(2) NUMBITS(1) [Enable = 1, Disable = 0],
+        SW OFFSET(10) NUMBITS(1) [Enable = 1, Disable = 0],
+        BRANCHPRED OFFSET(11) NUMBITS(1) [Enable = 1, Disable = 0],
         INSTR OFFSET(12) NUMBITS(1) [Enable = 1, Disable = 0],
         VECTOR OFFSET(13) NUMBITS(1) [High = 1, Low = 0],
         ALIGN OFFSET(22) NUMBITS(1) [],
@@ -30,27 +37,68 @@ register_bitfields! {u32,
         TEXREMAP OFFSET(28) NUMBITS(1) [Enable = 1, Disable = 0],
         ACCFLAG OFFSET(29) NUMBITS(1) [Enable = 1, Disable = 0],
         THUMBEXC OFFSET(30) NUMBITS(1) [Arm = 0, Thumb = 1]
+    ],
+    pub TTBR [
+        IRGN1 OFFSET(0) NUMBITS(1) [],
+        S OFFSET(1) NUMBITS(1) [NonShareable = 0, Shareable = 1],
+        IMP OFFSET(2) NUMBITS(1) [],
+        RGN OFFSET(3) NUMBITS(2) [
+            NonCacheable = 0b00,
+            WriteBackWriteAllocate = 0b01,
+            WriteThrough = 0b10,
+            WriteBackNoWriteAllocate = 0b11
+        ],
+        NOS OFFSET(5) NUMBITS(1) [OuterShareable = 0, InnerShareable = 1],
+        IRGN0 OFFSET(6) NUMBITS(1) []
+    ],
+    pub TTBCR [
+        N OFFSET(0) NUMBITS(3) [],
+        PD0 OFFSET(4) NUMBITS(1) [WalkEnabled = 0, WalkDisabled = 1],
+        PD1 OFFSET(5) NUMBITS(1) [WalkEnabled = 0, WalkDisabled = 1],
+        EAE OFFSET(31) NUMBITS(1) []
+    ],
+    pub CONTEXTIDR [
+        ASID OFFSET(0) NUMBITS(8) [],
+        PROCID OFFSET(8) NUMBITS(24) []
     ]
 }
 
 pub struct SystemControl;
 pub struct TranslationTableBase0;
 pub struct TranslationTableBase1;
+pub struct TranslationTableBaseControl;
+pub struct ContextId;
+pub struct DomainAccessControl;
 
 impl RegisterReadWrite<u32, SCTLR::Register> for SystemControl {
     sys_coproc_read_raw!(u32, "p15", "c1", "c0", "0", "0");
     sys_coproc_write_raw!(u32, "p15", "c1", "c0", "0", "0");
 }
-impl RegisterReadWrite<u32, ()> for TranslationTableBase0 {
+impl RegisterReadWrite<u32, TTBR::Register> for TranslationTableBase0 {
     sys_coproc_read_raw!(u32, "p15", "c2", "c0", "0", "0");
     sys_coproc_write_raw!(u32, "p15", "c2", "c0", "0", "0");
 }
 
-impl RegisterReadWrite<u32, ()> for TranslationTableBase1 {
+impl RegisterReadWrite<u32, TTBR::Register> for TranslationTableBase1 {
     sys_coproc_read_raw!(u32, "p15", "c2", "c0", "0", "1");
     sys_coproc_write_raw!(u32, "p15", "c2", "c0", "0", "1");
 }
 
+impl RegisterReadWrite<u32, TTBCR::Register> for TranslationTableBaseControl {
+    sys_coproc_read_raw!(u32, "p15", "c2", "c0", "0", "2");
+    sys_coproc_write_raw!(u32, "p15", "c2", "c0", "0", "2");
+}
+
+impl RegisterReadWrite<u32, ()> for DomainAccessControl {
+    sys_coproc_read_raw!(u32, "p15", "c3", "c0", "0", "0");
+    sys_coproc_write_raw!(u32, "p15", "c3", "c0", "0", "0");
+}
+
+impl RegisterReadWrite<u32, CONTEXTIDR::Register> for ContextId {
+    sys_coproc_read_raw!(u32, "p15", "c13", "c0", "0", "1");
+    sys_coproc_write_raw!(u32, "p15", "c13", "c0", "0", "1");
+}
+
 /// Public interface for the SCTLR
 pub static SCTLR: SystemControl = SystemControl {};
 
@@ -59,3 +107,177 @@ pub static TTBR0: TranslationTableBase0 = TranslationTableBase0 {};
 
 /// Public interface for the TTBR1
 pub static TTBR1: TranslationTableBase1 = TranslationTableBase1 {};
+
+/// Public interface for the TTBCR
+pub static TTBCR: TranslationTableBaseControl = TranslationTableBaseControl {};
+
+/// Public interface for the CONTEXTIDR
+pub static CONTEXTIDR: ContextId = ContextId {};
+
+/// Public interface for the DACR
+pub static DACR: DomainAccessControl = DomainAccessControl {};
+
+/// Access to a domain as configured in the DACR
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DomainAccess {
+    /// Any access generates a domain fault
+    NoAccess,
+    /// Accesses are checked against the permission bits of the translation
+    Client,
+    /// Reserved encoding, behaves like `NoAccess`
+    Reserved,
+    /// Accesses are not checked against the permission bits
+    Manager,
+}
+
+impl DomainAccess {
+    fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            0b00 => DomainAccess::NoAccess,
+            0b01 => DomainAccess::Client,
+            0b10 => DomainAccess::Reserved,
+            _ => DomainAccess::Manager,
+        }
+    }
+    fn bits(self) -> u32 {
+        match self {
+            DomainAccess::NoAccess => 0b00,
+            DomainAccess::Client => 0b01,
+            DomainAccess::Reserved => 0b10,
+            DomainAccess::Manager => 0b11,
+        }
+    }
+}
+
+/// Disable translation table walks for addresses translated with TTBR0
+///
+/// A TLB miss on such an address generates a translation fault. This can be used to trap
+/// accidental accesses to user space while running in a kernel-only context.
+pub fn disable_ttbr0_walks() {
+    TTBCR.modify(TTBCR::PD0::WalkDisabled);
+    asm::isb();
+}
+
+/// Enable translation table walks for addresses translated with TTBR0
+pub fn enable_ttbr0_walks() {
+    TTBCR.modify(TTBCR::PD0::WalkEnabled);
+    asm::isb();
+}
+
+/// Disable translation table walks for addresses translated with TTBR1
+///
+/// A TLB miss on such an address generates a translation fault.
+pub fn disable_ttbr1_walks() {
+    TTBCR.modify(TTBCR::PD1::WalkDisabled);
+    asm::isb();
+}
+
+/// Enable translation table walks for addresses translated with TTBR1
+pub fn enable_ttbr1_walks() {
+    TTBCR.modify(TTBCR::PD1::WalkEnabled);
+    asm::isb();
+}
+
+/// Number of domains in the DACR
+pub const DOMAIN_COUNT: u8 = 16;
+
+/// Read the access of a domain from the DACR
+///
+/// Returns `None` if `domain` is not smaller than `DOMAIN_COUNT`.
+pub fn domain_access(domain: u8) -> Option<DomainAccess> {
+    if domain >= DOMAIN_COUNT {
+        return None;
+    }
+    Some(DomainAccess::from_bits(DACR.get() >> (2 * domain as u32)))
+}
+
+/// Set the access of a domain in the DACR, followed by an `isb`
+///
+/// Fails with `PageError::IndexError` if `domain` is not smaller than `DOMAIN_COUNT`, the DACR
+/// is not modified in this case.
+pub fn set_domain_access(domain: u8, access: DomainAccess) -> Result<()> {
+    if domain >= DOMAIN_COUNT {
+        return Err(PageError::IndexError {
+            index: domain as usize,
+            size: DOMAIN_COUNT as usize,
+        });
+    }
+    let shift = 2 * domain as u32;
+    let dacr = (DACR.get() & !(0b11 << shift)) | (access.bits() << shift);
+    DACR.set(dacr);
+    asm::isb();
+    Ok(())
+}
+
+/// Returns whether the MMU is enabled
+pub fn mmu_enabled() -> bool {
+    SCTLR.is_set(SCTLR::MMU)
+}
+
+/// Returns whether the data and unified caches are enabled
+pub fn dcache_enabled() -> bool {
+    SCTLR.is_set(SCTLR::CACHE)
+}
+
+/// Returns whether the instruction cache is enabled
+pub fn icache_enabled() -> bool {
+    SCTLR.is_set(SCTLR::INSTR)
+}
+
+/// Returns whether branch prediction is enabled
+///
+/// On some cores branch prediction is always enabled and the bit reads as one.
+pub fn branch_prediction_enabled() -> bool {
+    SCTLR.is_set(SCTLR::BRANCHPRED)
+}
+
+/// Returns whether TEX remapping is enabled
+///
+/// With TEX remapping the memory type of a descriptor is taken from the PRRR and NMRR.
+pub fn tex_remap_enabled() -> bool {
+    SCTLR.is_set(SCTLR::TEXREMAP)
+}
+
+/// Enable the MMU
+///
+/// Invalidates the TLB and the branch predictor and sets SCTLR.M between the required barriers.
+/// A valid translation table has to be set in the ttbr0 before.
+pub fn enable_mmu() {
+    TLBIALL.set(0);
+    BPIALL.set(0);
+    asm::dsb();
+    SCTLR.modify(SCTLR::MMU::Enable);
+    asm::isb();
+}
+
+/// Disable the MMU
+pub fn disable_mmu() {
+    asm::dsb();
+    SCTLR.modify(SCTLR::MMU::Disable);
+    asm::isb();
+}
+
+/// Enable the data and instruction caches
+///
+/// The instruction cache is invalidated before. The data cache is not invalidated, since this
+/// would discard data if it was in use already.
+pub fn enable_caches() {
+    ICIALLU.set(0);
+    asm::dsb();
+    SCTLR.modify(SCTLR::CACHE::Enable + SCTLR::INSTR::Enable);
+    asm::isb();
+}
+
+/// Disable the data and instruction caches
+///
+/// The caches are disabled first and the data cache is cleaned and invalidated afterwards, so that
+/// no line can be allocated or dirtied between the clean and the disable.
+pub fn disable_caches() {
+    SCTLR.modify(SCTLR::CACHE::Disable + SCTLR::INSTR::Disable);
+    asm::isb();
+    // Ends with a dsb
+    clean_invalidate_dcache_all();
+    ICIALLU.set(0);
+    asm::dsb();
+    asm::isb();
+}
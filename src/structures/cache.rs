@@ -0,0 +1,241 @@
+//! Module for cache related issues
+//!
+//! To print the size of the level 1 data cache use
+//! ```
+//!     if let Some(geometry) = cache_geometry(1, false) {
+//!         println!("L1 data cache: {} bytes", geometry.total_size);
+//!     }
+//! ```
+//!
+//! After writing instructions to memory make them visible to the instruction fetch by
+//! ```
+//!     sync_instruction_memory(code_addr, code_len);
+//! ```
+//!
+//! # Point of unification and point of coherency
+//! Data cache lines can be cleaned to two different points:
+//! * The point of unification (PoU) is where the instruction and data caches and the translation
+//!   table walks of a core see the same copy of memory. Cleaning to the PoU is sufficient for
+//!   self-modifying code, but the data might still sit in an outer cache.
+//! * The point of coherency (PoC) is where all observers, including DMA masters, see the same copy
+//!   of memory. Buffers shared with a device have to be cleaned to the PoC.
+//!
+//! Using the PoU variant for DMA buffers works on systems without outer caches and fails
+//! intermittently on all others. Before a device reads a buffer call
+//! ```
+//!     dma_prepare_to_device(buffer_addr, buffer_len);
+//! ```
+//! and after a device has written a buffer call
+//! ```
+//!     dma_complete_from_device(buffer_addr, buffer_len);
+//! ```
+
+use crate::asm;
+use crate::regs::cache_maintenance::*;
+use crate::regs::identification::*;
+use crate::VirtualAddress;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Decoded size information of a single cache
+pub struct CacheGeometry {
+    /// Length of a cache line in bytes
+    pub line_size_bytes: u32,
+    /// Number of ways
+    pub associativity: u32,
+    /// Number of sets
+    pub num_sets: u32,
+    /// Size of the cache in bytes
+    pub total_size: u32,
+}
+
+/// Read the geometry of a cache
+///
+/// `level` counts from 1 like in the ARM ARM. Returns `None` if the CLIDR reports that there is no
+/// cache of the requested kind at this level.
+pub fn cache_geometry(level: u8, is_instruction: bool) -> Option<CacheGeometry> {
+    if level == 0 || level > 7 {
+        return None;
+    }
+    // Cache type of the level: 0b001 instruction only, 0b010 data only, 0b011 separate
+    // instruction and data caches, 0b100 unified cache
+    let ctype = (CLIDR.get() >> (3 * (level as u32 - 1))) & 0b111;
+    let present = match (ctype, is_instruction) {
+        (0b001, true) | (0b011, true) => true,
+        (0b010, false) | (0b011, false) | (0b100, false) => true,
+        _ => false,
+    };
+    if !present {
+        return None;
+    }
+    let selection = if is_instruction {
+        CSSELR::IND::Instruction
+    } else {
+        CSSELR::IND::Data
+    };
+    CSSELR.write(CSSELR::LEVEL.val(level as u32 - 1) + selection);
+    // The CCSIDR is only guaranteed to reflect the new selection after an isb
+    asm::isb();
+    let ccsidr = CCSIDR.extract();
+    // The fields are encoded as log2(words) - 2, ways - 1 and sets - 1
+    let line_size_bytes = 1 << (ccsidr.read(CCSIDR::LINESIZE) + 4);
+    let associativity = ccsidr.read(CCSIDR::ASSOCIATIVITY) + 1;
+    let num_sets = ccsidr.read(CCSIDR::NUMSETS) + 1;
+    Some(CacheGeometry {
+        line_size_bytes,
+        associativity,
+        num_sets,
+        total_size: line_size_bytes * associativity * num_sets,
+    })
+}
+
+/// Smallest instruction cache line size in bytes
+pub fn icache_line_size() -> u32 {
+    // The field is the log2 of the number of words
+    4 << CTR.read(CTR::IMINLINE)
+}
+
+/// Smallest data cache line size in bytes
+pub fn dcache_line_size() -> u32 {
+    4 << CTR.read(CTR::DMINLINE)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Smallest cache line sizes of the core as reported by the CTR
+pub struct CacheLineSize {
+    /// Smallest instruction cache line size in bytes
+    pub instruction: u32,
+    /// Smallest data cache line size in bytes
+    pub data: u32,
+}
+
+impl CacheLineSize {
+    /// Read the line sizes from the CTR
+    pub fn read() -> Self {
+        Self {
+            instruction: icache_line_size(),
+            data: dcache_line_size(),
+        }
+    }
+    /// Returns whether a buffer covers only whole data cache lines
+    ///
+    /// Buffers written by a DMA master should satisfy this, otherwise
+    /// `dma_complete_from_device` discards writes to the data sharing the first or last line.
+    pub fn is_data_aligned(self, addr: VirtualAddress, len: u32) -> bool {
+        let mask = self.data - 1;
+        addr.as_u32() & mask == 0 && len & mask == 0
+    }
+}
+
+/// Iterate over the start addresses of all cache lines overlapping the range
+fn for_each_line<F: FnMut(u32)>(addr: VirtualAddress, len: u32, line_size: u32, mut f: F) {
+    let end = addr.as_u32().saturating_add(len);
+    let mut line = addr.as_u32() & !(line_size - 1);
+    while line < end {
+        f(line);
+        line = match line.checked_add(line_size) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+}
+
+/// Invalidate all branch predictor entries
+pub fn invalidate_bpred_all() {
+    BPIALL.set(0);
+}
+
+/// Invalidate the instruction cache lines of a memory range to the point of unification
+pub fn invalidate_icache_range(addr: VirtualAddress, len: u32) {
+    for_each_line(addr, len, icache_line_size(), |line| ICIMVAU.set(line));
+}
+
+/// Clean the data cache line containing the address to the point of coherency
+pub fn clean_dcache_by_mva_poc(addr: VirtualAddress) {
+    DCCMVAC.set(addr.as_u32());
+}
+
+/// Clean the data cache line containing the address to the point of unification
+pub fn clean_dcache_by_mva_pou(addr: VirtualAddress) {
+    DCCMVAU.set(addr.as_u32());
+}
+
+/// Clean the data cache lines of a memory range to the point of coherency
+pub fn clean_dcache_range_poc(addr: VirtualAddress, len: u32) {
+    for_each_line(addr, len, dcache_line_size(), |line| DCCMVAC.set(line));
+}
+
+/// Clean the data cache lines of a memory range to the point of unification
+pub fn clean_dcache_range_pou(addr: VirtualAddress, len: u32) {
+    for_each_line(addr, len, dcache_line_size(), |line| DCCMVAU.set(line));
+}
+
+/// Invalidate the data cache lines of a memory range to the point of coherency
+///
+/// Lines that are only partially covered by the range are invalidated as a whole, which discards
+/// all writes to the rest of the line.
+pub fn invalidate_dcache_range_poc(addr: VirtualAddress, len: u32) {
+    for_each_line(addr, len, dcache_line_size(), |line| DCIMVAC.set(line));
+}
+
+/// Prepare a buffer that is read by a DMA master
+///
+/// Cleans the buffer to the point of coherency.
+pub fn dma_prepare_to_device(addr: VirtualAddress, len: u32) {
+    clean_dcache_range_poc(addr, len);
+    asm::dsb();
+}
+
+/// Make the data a DMA master wrote to a buffer visible to the core
+///
+/// Invalidates the buffer to the point of coherency. The buffer should be aligned to the cache
+/// line size, see `invalidate_dcache_range_poc` and `CacheLineSize::is_data_aligned`.
+pub fn dma_complete_from_device(addr: VirtualAddress, len: u32) {
+    invalidate_dcache_range_poc(addr, len);
+    asm::dsb();
+}
+
+/// Make instructions written to a memory range visible to the instruction fetch
+///
+/// Cleans the data cache to the point of unification, invalidates the instruction cache and the
+/// branch predictor for the range and synchronizes with barriers.
+pub fn sync_instruction_memory(addr: VirtualAddress, len: u32) {
+    clean_dcache_range_pou(addr, len);
+    asm::dsb();
+    invalidate_icache_range(addr, len);
+    invalidate_bpred_all();
+    asm::dsb();
+    asm::isb();
+}
+
+/// Clean and invalidate all data and unified caches up to the point of coherency
+///
+/// The caches are maintained by set and way, level by level as reported by the CLIDR.
+pub fn clean_invalidate_dcache_all() {
+    let clidr = CLIDR.extract();
+    let level_of_coherency = clidr.read(CLIDR::LOC);
+    for level in 0..level_of_coherency {
+        // Only data and unified caches have to be maintained
+        let ctype = (clidr.get() >> (3 * level)) & 0b111;
+        if ctype < 0b010 {
+            continue;
+        }
+        CSSELR.write(CSSELR::LEVEL.val(level) + CSSELR::IND::Data);
+        asm::isb();
+        let ccsidr = CCSIDR.extract();
+        let line_shift = ccsidr.read(CCSIDR::LINESIZE) + 4;
+        let max_way = ccsidr.read(CCSIDR::ASSOCIATIVITY);
+        let max_set = ccsidr.read(CCSIDR::NUMSETS);
+        // The way is stored in the top bits of the operand
+        let way_shift = if max_way == 0 {
+            0
+        } else {
+            max_way.leading_zeros()
+        };
+        for way in 0..=max_way {
+            for set in 0..=max_set {
+                DCCISW.set((way << way_shift) | (set << line_shift) | (level << 1));
+            }
+        }
+    }
+    asm::dsb();
+}
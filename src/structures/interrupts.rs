@@ -1,9 +1,16 @@
 //! Module for creating interrupt handlers
 
+use crate::asm;
+use crate::regs::identification::has_virtualization_extensions;
 use crate::regs::security::*;
 use crate::regs::vmem_control::*;
+use crate::structures::cache::{invalidate_bpred_all, sync_instruction_memory};
+use crate::structures::mmio::Mmio;
+use crate::structures::paging::{PageError, Result};
 use crate::VirtualAddress;
-use core::ops;
+use core::cmp;
+use core::mem;
+use core::ptr;
 use register::mmio::*;
 
 #[repr(C)]
@@ -26,35 +33,6 @@ struct RegisterBlock {
     fiq_addr: ReadWrite<u32, ()>,
 }
 
-#[repr(transparent)]
-struct VectorTableMemory {
-    memory_addr: u32,
-}
-
-impl ops::Deref for VectorTableMemory {
-    type Target = RegisterBlock;
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*self.ptr() }
-    }
-}
-
-impl VectorTableMemory {
-    fn new() -> Self {
-        let table_addr = if SCTLR.is_set(SCTLR::VECTOR) {
-            0xffff_0000
-        } else {
-            // We might have to check whether this register is there
-            VBAR.get()
-        };
-        VectorTableMemory {
-            memory_addr: table_addr,
-        }
-    }
-    fn ptr(&self) -> *mut RegisterBlock {
-        self.memory_addr as *mut _
-    }
-}
-
 pub fn get_vectortable_address() -> VirtualAddress {
     let table_addr = if SCTLR.is_set(SCTLR::VECTOR) {
         0xffff_0000
@@ -65,8 +43,32 @@ pub fn get_vectortable_address() -> VirtualAddress {
     VirtualAddress::new(table_addr)
 }
 
+/// Move the vector table to `base` by writing the VBAR
+///
+/// The VBAR ignores the low 5 bits, hence `base` has to be aligned to 32 bytes, otherwise
+/// `PageError::AlignError` is returned. The write is followed by invalidating the branch
+/// predictor and an `isb`, so that the next exception uses the new vector table.
+/// `VectorTable::new` picks up the new base unless the high vectors are selected in the SCTLR.
+///
+/// # Safety
+/// `base` has to point to a valid vector table.
+pub unsafe fn relocate_vector_table(base: VirtualAddress) -> Result<()> {
+    if base.as_u32() & 0x1f != 0 {
+        return Err(PageError::AlignError {
+            addr: base.as_u32(),
+            align: 32,
+        });
+    }
+    VBAR.set(base.as_u32());
+    // Predictions for the old vectors must not be used
+    invalidate_bpred_all();
+    asm::dsb();
+    asm::isb();
+    Ok(())
+}
+
 pub struct VectorTable {
-    vectors: VectorTableMemory,
+    vectors: Mmio<RegisterBlock>,
 }
 
 impl VectorTable {
@@ -76,16 +78,26 @@ impl VectorTable {
     /// The caller has to garantee that the address set in the SCTLR.V register or in the VBAR
     /// register points to valid memory
     pub fn new() -> Self {
-        let mem = VectorTableMemory::new();
+        let mem = unsafe { Mmio::new(get_vectortable_address()) };
         VectorTable { vectors: mem }
     }
+    /// Set all vectors to jump to `initial_address`
+    ///
+    /// The vector at offset 0x14 is only taken by cores with the Virtualization Extensions, where
+    /// it is the Hyp trap vector of the Hyp vector table. On all other cores the slot is unused and
+    /// it is not written. Note that the Monitor vector table (see `MVBAR`) of cores with the
+    /// Security Extensions uses offset 0x08 for Secure Monitor calls, use `set_swi_handler` for
+    /// it.
     pub fn init(&self, initial_address: VirtualAddress) {
+        let has_hyp = has_virtualization_extensions();
         self.vectors.reset_handler.set(ASM_PC_24);
         self.vectors.undef_handler.set(ASM_PC_24);
         self.vectors.swi_handler.set(ASM_PC_24);
         self.vectors.prefetch_handler.set(ASM_PC_24);
         self.vectors.data_handler.set(ASM_PC_24);
-        self.vectors.hyp_handler.set(ASM_PC_24);
+        if has_hyp {
+            self.vectors.hyp_handler.set(ASM_PC_24);
+        }
         self.vectors.irq_handler.set(ASM_PC_24);
         self.vectors.fiq_handler.set(ASM_PC_24);
         self.vectors.reset_addr.set(initial_address.as_u32());
@@ -93,10 +105,36 @@ impl VectorTable {
         self.vectors.swi_addr.set(initial_address.as_u32());
         self.vectors.prefetch_addr.set(initial_address.as_u32());
         self.vectors.data_addr.set(initial_address.as_u32());
-        self.vectors.hyp_addr.set(initial_address.as_u32());
+        if has_hyp {
+            self.vectors.hyp_addr.set(initial_address.as_u32());
+        }
         self.vectors.irq_addr.set(initial_address.as_u32());
         self.vectors.fiq_addr.set(initial_address.as_u32());
     }
+    /// Make the changes to the vector table visible to the instruction fetch
+    ///
+    /// The vector table is code, hence writing to it is self-modifying code. Call this after a
+    /// batch of `init` and `set_*_handler` calls, otherwise an exception might still use the old
+    /// handler. Cleans the data cache, invalidates the instruction cache and the branch predictor
+    /// and synchronizes with barriers.
+    pub fn sync(&self) {
+        sync_instruction_memory(self.vectors.addr(), mem::size_of::<RegisterBlock>() as u32);
+    }
+    /// Install a trampoline in this vector table
+    ///
+    /// # Safety
+    /// The address slot of the trampoline has to be inside the memory of the vector table.
+    pub unsafe fn set_trampoline(&self, trampoline: &VectorTableTrampoline) {
+        trampoline.write(self.vectors.addr());
+    }
+    /// Set the reset handler
+    ///
+    /// Only used by the Secure or Non-secure PL1 vector table, the reset vector of the Monitor
+    /// and Hyp vector tables is never taken. This is used for warm resets and on systems where
+    /// the boot ROM starts secondary cores through the reset vector of the vector table.
+    pub fn set_reset_handler(&self, handler: VirtualAddress) {
+        self.vectors.reset_addr.set(handler.as_u32());
+    }
     pub fn set_undef_handler(&self, handler: VirtualAddress) {
         self.vectors.undef_addr.set(handler.as_u32());
     }
@@ -109,6 +147,13 @@ impl VectorTable {
     pub fn set_data_abort_handler(&self, handler: VirtualAddress) {
         self.vectors.data_addr.set(handler.as_u32());
     }
+    /// Address of the current data abort handler
+    pub fn data_abort_handler(&self) -> VirtualAddress {
+        VirtualAddress::new(self.vectors.data_addr.get())
+    }
+    /// Set the Hyp trap handler
+    ///
+    /// Only taken on cores with the Virtualization Extensions.
     pub fn set_hyp_handler(&self, handler: VirtualAddress) {
         self.vectors.hyp_addr.set(handler.as_u32());
     }
@@ -120,6 +165,29 @@ impl VectorTable {
     }
 }
 
+/// Run `f` with `temp` installed as the data abort handler
+///
+/// The current handler is saved before and restored after `f` returns, both changes are made
+/// visible to the instruction fetch with `VectorTable::sync`. This allows probing memory that
+/// might fault, with `temp` handling the expected abort. Handler changes of `f` to the data abort
+/// vector are overwritten by the restore.
+///
+/// The vector table is shared by all exceptions of the core, hence interrupts whose handlers
+/// might fault should be masked while `f` runs.
+pub fn with_data_abort_handler<F, R>(temp: VirtualAddress, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let table = VectorTable::new();
+    let saved = table.data_abort_handler();
+    table.set_data_abort_handler(temp);
+    table.sync();
+    let result = f();
+    table.set_data_abort_handler(saved);
+    table.sync();
+    result
+}
+
 impl Default for VectorTable {
     fn default() -> Self {
         Self::new()
@@ -132,3 +200,176 @@ pub const fn asm_ldr_pc(offset: u8) -> u32 {
     let instruction = u32::swap_bytes(0x00f0_9fe5);
     instruction | (offset as u32)
 }
+
+/// Size of the branch instructions at the start of the vector table
+const VECTOR_INSTRUCTIONS_SIZE: u32 = 0x20;
+
+/// Entries of the vector table in the order of their offsets
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Vector {
+    Reset,
+    Undefined,
+    SupervisorCall,
+    PrefetchAbort,
+    DataAbort,
+    Hyp,
+    Irq,
+    Fiq,
+}
+
+impl Vector {
+    /// Offset of the vector from the start of the vector table
+    pub const fn offset(self) -> u32 {
+        self as u32 * 4
+    }
+}
+
+/// A `ldr pc, [pc, #imm]` instruction at a vector together with the handler address it loads
+///
+/// The vector table built by `VectorTable::init` stores the handler address of every vector 0x20
+/// bytes after the vector, which gives the immediate 0x18 since the pc reads as the address of the
+/// instruction plus 8. The trampoline allows any layout where the address slot is behind the
+/// branch instructions and reachable with the 12 bit immediate.
+#[derive(Copy, Clone, Debug)]
+pub struct VectorTableTrampoline {
+    vector: Vector,
+    addr_offset: u32,
+    handler: VirtualAddress,
+}
+
+impl VectorTableTrampoline {
+    /// Create a trampoline for `vector` that loads `handler` from `addr_offset`
+    ///
+    /// `addr_offset` is the offset of the address slot from the start of the vector table. It has
+    /// to be word aligned, must not overlap the branch instructions and has to be in reach of the
+    /// 12 bit immediate of the `ldr` instruction.
+    pub fn new(vector: Vector, addr_offset: u32, handler: VirtualAddress) -> Result<Self> {
+        if addr_offset & 0b11 != 0 {
+            return Err(PageError::AlignError {
+                addr: addr_offset,
+                align: 4,
+            });
+        }
+        // The pc reads as the address of the instruction plus 8
+        let pc = vector.offset() + 8;
+        let start = cmp::max(pc, VECTOR_INSTRUCTIONS_SIZE);
+        if addr_offset < start || addr_offset - pc > 0xfff {
+            return Err(PageError::NotInRange {
+                addr: addr_offset,
+                start,
+                // Offsets up to pc + 0xfff are encodable
+                size: pc + 0x1000 - start,
+            });
+        }
+        Ok(VectorTableTrampoline {
+            vector,
+            addr_offset,
+            handler,
+        })
+    }
+    /// The `ldr pc, [pc, #imm]` instruction word placed at the vector
+    pub fn instruction(&self) -> u32 {
+        let imm = self.addr_offset - (self.vector.offset() + 8);
+        u32::swap_bytes(0x00f0_9fe5) | imm
+    }
+    /// The vector the trampoline is placed at
+    pub fn vector(&self) -> Vector {
+        self.vector
+    }
+    /// Offset of the handler address from the start of the vector table
+    pub fn addr_offset(&self) -> u32 {
+        self.addr_offset
+    }
+    /// The handler the trampoline branches to
+    pub fn handler(&self) -> VirtualAddress {
+        self.handler
+    }
+    /// Write the instruction and the handler address to the vector table at `table_base`
+    ///
+    /// Call `VectorTable::sync` or `sync_instruction_memory` afterwards.
+    ///
+    /// # Safety
+    /// `table_base` has to point to a writable vector table that is large enough to hold the
+    /// address slot.
+    pub unsafe fn write(&self, table_base: VirtualAddress) {
+        let instruction: *mut u32 = (table_base + self.vector.offset()).as_mut_ptr();
+        let addr: *mut u32 = (table_base + self.addr_offset).as_mut_ptr();
+        ptr::write_volatile(addr, self.handler.as_u32());
+        ptr::write_volatile(instruction, self.instruction());
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+/// Number of an interrupt source at the interrupt controller
+pub struct IrqNumber(u16);
+
+impl IrqNumber {
+    /// Create a new interrupt number
+    pub const fn new(irq: u16) -> Self {
+        IrqNumber(irq)
+    }
+    /// Converts the interrupt number to an unsigned integer
+    pub const fn as_u16(self) -> u16 {
+        self.0
+    }
+}
+
+/// Interface to the interrupt controller used by an IRQ dispatcher
+pub trait InterruptController {
+    /// Acknowledge the highest priority pending interrupt, returns `None` for spurious interrupts
+    fn acknowledge(&self) -> Option<IrqNumber>;
+    /// Signal that the interrupt has been handled
+    fn end_of_interrupt(&self, irq: IrqNumber);
+}
+
+/// Placeholder controller that never reports an interrupt
+///
+/// `acknowledge` always returns `None`, hence a dispatcher treats every IRQ as spurious and no
+/// handler runs. Only use this to satisfy the type parameter until a real driver for the
+/// interrupt controller of the system, e.g. the GIC, is available.
+pub struct NoController;
+
+impl InterruptController for NoController {
+    fn acknowledge(&self) -> Option<IrqNumber> {
+        None
+    }
+    fn end_of_interrupt(&self, _irq: IrqNumber) {}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Exceptions taken to a PL1 mode
+pub enum ExceptionKind {
+    Undefined,
+    SupervisorCall,
+    SecureMonitorCall,
+    PrefetchAbort,
+    DataAbort,
+    Irq,
+    Fiq,
+}
+
+/// Offset between the banked LR and the preferred return address of an exception
+///
+/// `thumb` is the instruction set state in which the exception was taken, i.e. SPSR.T. The values
+/// are taken from the table "Offsets applied to Link value for exceptions taken to PL1 modes" in
+/// the ARM ARM. Subtracting the offset from the LR gives
+/// * the undefined instruction for `Undefined`,
+/// * the instruction after the call for `SupervisorCall` and `SecureMonitorCall`,
+/// * the aborted instruction for `PrefetchAbort` and `DataAbort`,
+/// * the next instruction that has not been executed for `Irq` and `Fiq`.
+pub fn exception_return_offset(kind: ExceptionKind, thumb: bool) -> u32 {
+    match kind {
+        ExceptionKind::Undefined => {
+            if thumb {
+                2
+            } else {
+                4
+            }
+        }
+        ExceptionKind::SupervisorCall | ExceptionKind::SecureMonitorCall => 0,
+        ExceptionKind::PrefetchAbort => 4,
+        ExceptionKind::DataAbort => 8,
+        ExceptionKind::Irq | ExceptionKind::Fiq => 4,
+    }
+}
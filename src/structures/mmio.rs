@@ -0,0 +1,53 @@
+//! Module for memory mapped register blocks
+//!
+//! Define the register block of a device
+//! ```
+//!     #[repr(C)]
+//!     struct UartRegisters {
+//!         data: ReadWrite<u32, ()>,
+//!         status: ReadOnly<u32, ()>,
+//!     }
+//! ```
+//! and access it at the virtual address given by the device mapper
+//! ```
+//!     let uart: Mmio<UartRegisters> = unsafe { mapper.mmio(uart_phys_addr)? };
+//!     uart.data.set(0x41);
+//! ```
+
+use crate::VirtualAddress;
+use core::marker::PhantomData;
+use core::ops;
+
+/// Pointer to a memory mapped register block
+pub struct Mmio<T> {
+    addr: VirtualAddress,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Mmio<T> {
+    /// Create a pointer to a register block at the given address
+    ///
+    /// # Safety
+    /// The caller has to garantee that the address is mapped and points to a register block with
+    /// the layout of `T` for the lifetime of the returned value.
+    pub const unsafe fn new(addr: VirtualAddress) -> Self {
+        Mmio {
+            addr,
+            phantom: PhantomData,
+        }
+    }
+    /// Returns the address of the register block
+    pub fn addr(&self) -> VirtualAddress {
+        self.addr
+    }
+    fn ptr(&self) -> *const T {
+        self.addr.as_u32() as usize as *const T
+    }
+}
+
+impl<T> ops::Deref for Mmio<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.ptr() }
+    }
+}
@@ -1,4 +1,9 @@
 //! Structures
 
+pub mod cache;
 pub mod interrupts;
+pub mod mmio;
 pub mod paging;
+#[cfg(feature = "selftest")]
+pub mod selftest;
+pub mod stack;
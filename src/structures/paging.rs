@@ -6,7 +6,7 @@
 //! ```
 //! Create a reference
 //! ```
-//!     let base_table = TranslationTable::new(&mut TTRB0);
+//!     let base_table = TranslationTable::from_static(unsafe { &mut TTBR0 });
 //! ```
 //! To create new entries in the table, first create a new memory attribute by
 //! ```
@@ -33,16 +33,29 @@
 //!     unsafe { pagetable.table_mut()[index_pt] = small_page };
 //! ```
 
+use crate::asm;
+use crate::regs::identification::has_multiprocessing_extensions;
+use crate::regs::tlb_maintenance::*;
 use crate::regs::vmem_control::*;
-use crate::{PhysicalAddress, VirtualAddress};
-use core::arch::arm;
+use crate::structures::mmio::Mmio;
+use crate::util::{BitMaskIter, HexWord};
+use crate::{PhysicalAddress, PhysicalAddress64, VirtualAddress};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use core::alloc::Layout;
+use core::cmp;
+use core::convert::TryFrom;
 use core::fmt;
+use core::mem;
 use core::ops;
-use register::{register_bitfields, FieldValue};
+use core::ptr;
+use core::slice;
+use register::{register_bitfields, FieldValue, LocalRegisterCopy};
 
 register_bitfields! {
     u32,
-    ATTRIBUTES [
+    pub ATTRIBUTES [
         PXN OFFSET(0) NUMBITS(1) [Enable = 0b1],
         B OFFSET(2) NUMBITS(1) [Enable = 0b1],
         C OFFSET(3) NUMBITS(1) [Enable = 0b1],
@@ -85,7 +98,76 @@ pub struct MemoryAttributes(u32);
 // all higher bits are reserved (read/write 0)
 //
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Memory types that can be encoded without TEX remapping
+///
+/// The encoding in the TEX, C, and B bits with SCTLR.TRE = 0 is
+///
+/// | Memory type                                | TEX   | C | B |
+/// |--------------------------------------------|-------|---|---|
+/// | `StronglyOrdered`                          | 0b000 | 0 | 0 |
+/// | `SharedDevice`                             | 0b000 | 0 | 1 |
+/// | `NormalWriteThrough`                       | 0b000 | 1 | 0 |
+/// | `NormalWriteBackNoWriteAllocate`           | 0b000 | 1 | 1 |
+/// | `NormalNonCacheable`                       | 0b001 | 0 | 0 |
+/// | `NormalWriteBackWriteAllocate`             | 0b001 | 1 | 1 |
+/// | `NonSharedDevice`                          | 0b010 | 0 | 0 |
+///
+/// With SCTLR.TRE = 1 the bits TEX[0], C, and B form an index into the PRRR and NMRR registers
+/// instead, see `MemoryAttributes::remap_index`.
+pub enum MemoryType {
+    StronglyOrdered,
+    SharedDevice,
+    NonSharedDevice,
+    NormalNonCacheable,
+    NormalWriteThrough,
+    NormalWriteBackNoWriteAllocate,
+    NormalWriteBackWriteAllocate,
+}
+
+impl MemoryType {
+    // TEX, C, and B in the layout of the memory attributes
+    fn bits(self) -> u32 {
+        match self {
+            MemoryType::StronglyOrdered => 0x0000,
+            MemoryType::SharedDevice => 0x0004,
+            MemoryType::NormalWriteThrough => 0x0008,
+            MemoryType::NormalWriteBackNoWriteAllocate => 0x000c,
+            MemoryType::NormalNonCacheable => 0x1000,
+            MemoryType::NormalWriteBackWriteAllocate => 0x100c,
+            MemoryType::NonSharedDevice => 0x2000,
+        }
+    }
+}
+
 impl MemoryAttributes {
+    /// Set the memory type, assuming that TEX remapping is disabled
+    pub fn with_memory_type(self, memory_type: MemoryType) -> Self {
+        MemoryAttributes((self.0 & !0x700c) | memory_type.bits())
+    }
+    /// Decode the memory type, assuming that TEX remapping is disabled
+    ///
+    /// Returns `None` for the implementation defined and reserved encodings and for the cacheable
+    /// memory types with different inner and outer policies (TEX = 0b1xx).
+    pub fn memory_type(self) -> Option<MemoryType> {
+        let memory_type = match self.0 & 0x700c {
+            0x0000 => MemoryType::StronglyOrdered,
+            0x0004 => MemoryType::SharedDevice,
+            0x0008 => MemoryType::NormalWriteThrough,
+            0x000c => MemoryType::NormalWriteBackNoWriteAllocate,
+            0x1000 => MemoryType::NormalNonCacheable,
+            0x100c => MemoryType::NormalWriteBackWriteAllocate,
+            0x2000 => MemoryType::NonSharedDevice,
+            _ => return None,
+        };
+        Some(memory_type)
+    }
+    /// Index into the PRRR and NMRR registers if TEX remapping is enabled
+    ///
+    /// The index is given by TEX[0], C, and B.
+    pub fn remap_index(self) -> u8 {
+        (((self.0 >> 10) & 0b100) | ((self.0 >> 2) & 0b11)) as u8
+    }
     pub fn from_table_descriptor(table_descriptor: TranslationTableDescriptor) -> Option<Self> {
         let table_type = table_descriptor.get_type();
         let val = table_descriptor.0;
@@ -139,6 +221,26 @@ impl MemoryAttributes {
             }
         }
     }
+    /// Raw value of the attributes in the layout of a section descriptor
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+    /// View of the attributes for reading single fields
+    pub fn fields(self) -> LocalRegisterCopy<u32, ATTRIBUTES::Register> {
+        LocalRegisterCopy::new(self.0)
+    }
+    /// Access permission bits AP[1:0]
+    pub fn access_permission(self) -> Option<ATTRIBUTES::AP::Value> {
+        self.fields().read_as_enum(ATTRIBUTES::AP)
+    }
+    /// Type extension bits
+    pub fn tex(self) -> u8 {
+        self.fields().read(ATTRIBUTES::TEX) as u8
+    }
+    /// Domain of the memory region
+    pub fn domain(self) -> u8 {
+        self.fields().read(ATTRIBUTES::DOMAIN) as u8
+    }
     // For the cleaniness of the code, we have these functions here. They are only called in the
     // constructors for the respective descriptors, but the descriptors should not know about the
     // internal implementation of the memory attributes.
@@ -156,7 +258,8 @@ impl MemoryAttributes {
                 PageTableDescriptor(val)
             }
             PageTableType::LargePage => {
-                let mut val = 0b1 | (self.0 & 0b1100);
+                // C, B, and TEX are at the same position as in the memory attributes
+                let mut val = 0b1 | (self.0 & 0x700c);
                 // Copy AP
                 val |= (self.0 & 0xc00) >> (10 - 4);
                 // Copy AP2, S, and nG
@@ -191,6 +294,20 @@ impl From<FieldValue<u32, ATTRIBUTES::Register>> for MemoryAttributes {
     }
 }
 
+impl ops::BitOr for MemoryAttributes {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        MemoryAttributes(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOr<FieldValue<u32, ATTRIBUTES::Register>> for MemoryAttributes {
+    type Output = Self;
+    fn bitor(self, rhs: FieldValue<u32, ATTRIBUTES::Register>) -> Self {
+        self | MemoryAttributes::from(rhs)
+    }
+}
+
 impl Default for MemoryAttributes {
     fn default() -> Self {
         MemoryAttributes(0)
@@ -198,25 +315,99 @@ impl Default for MemoryAttributes {
 }
 
 #[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum PageError {
-    AlignError,
+    /// The address is not a multiple of `align`
+    AlignError {
+        addr: u32,
+        align: u32,
+    },
     TranslationError,
     DomainError,
     PermissionError,
     InvalidMemory,
-    NotInRange,
-    IndexError,
+    /// The address is not in the range of `size` bytes starting at `start`
+    NotInRange {
+        addr: u32,
+        start: u32,
+        size: u32,
+    },
+    /// The index is not smaller than the size of the table
+    IndexError {
+        index: usize,
+        size: usize,
+    },
+    SizeError,
+    /// The entry at the index of the translation table is already mapped
+    AlreadyMapped {
+        index: usize,
+    },
+    /// The value read back from a register differs from the value written
+    RegisterMismatch,
+    /// The virtual address translates to a different physical address than expected
+    MappingMismatch {
+        virt: u32,
+        expected: u32,
+        actual: u32,
+    },
+    /// The processor mode cannot be entered from the current mode
+    ModeError,
 }
 
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageError::AlignError { addr, align } => write!(
+                f,
+                "address {:#010x} is not aligned, needed {:#x} byte alignment",
+                addr, align
+            ),
+            PageError::TranslationError => f.write_str("translation fault"),
+            PageError::DomainError => f.write_str("domain fault"),
+            PageError::PermissionError => f.write_str("permission fault"),
+            PageError::InvalidMemory => f.write_str("invalid memory"),
+            PageError::NotInRange { addr, start, size } => write!(
+                f,
+                "address {:#010x} is not in the range {:#010x} with size {:#x}",
+                addr, start, size
+            ),
+            PageError::IndexError { index, size } => {
+                write!(f, "index {} is out of bounds for size {}", index, size)
+            }
+            PageError::SizeError => f.write_str("invalid size"),
+            PageError::AlreadyMapped { index } => write!(f, "entry {} is already mapped", index),
+            PageError::RegisterMismatch => f.write_str("register does not hold the written value"),
+            PageError::MappingMismatch {
+                virt,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "address {:#010x} translates to {:#010x}, expected {:#010x}",
+                virt, actual, expected
+            ),
+            PageError::ModeError => f.write_str("mode cannot be entered from the current mode"),
+        }
+    }
+}
+
+#[cfg(feature = "error-in-core")]
+impl core::error::Error for PageError {}
+
 pub type Result<T> = ::core::result::Result<T, PageError>;
 
 trait Alignable {
     fn is_aligned(&self, mask: u32) -> bool;
+    fn raw(&self) -> u32;
+    /// Checks the alignment, `mask` is the required alignment minus one
     fn check_align(&self, mask: u32) -> Result<()> {
         if self.is_aligned(mask) {
             Ok(())
         } else {
-            Err(PageError::AlignError)
+            Err(PageError::AlignError {
+                addr: self.raw(),
+                align: mask.wrapping_add(1),
+            })
         }
     }
     fn align(&mut self, mask: u32);
@@ -225,6 +416,9 @@ impl Alignable for VirtualAddress {
     fn is_aligned(&self, mask: u32) -> bool {
         (self.0 & mask) == 0
     }
+    fn raw(&self) -> u32 {
+        self.0
+    }
     fn align(&mut self, mask: u32) {
         self.0 |= !mask;
     }
@@ -233,6 +427,9 @@ impl Alignable for PhysicalAddress {
     fn is_aligned(&self, mask: u32) -> bool {
         (self.0 & mask) == 0
     }
+    fn raw(&self) -> u32 {
+        self.0
+    }
     fn align(&mut self, mask: u32) {
         self.0 |= !mask;
     }
@@ -276,18 +473,39 @@ impl DeviceVmemMapper {
     }
 
     /// Perform the mapping
+    ///
+    /// Fails with `PageError::AlreadyMapped` if the virtual address range already contains valid
+    /// entries, unless `force` is set.
     /// # Safety
     /// This function should only be called once and the virtual address range has to be empty.
     // Actually, calling the function twice does not hurt
-    pub unsafe fn do_mapping(&self, base_table: &mut TranslationTable) -> Result<()> {
+    pub unsafe fn do_mapping(&self, base_table: &mut TranslationTable, force: bool) -> Result<()> {
         let attributes =
             MemoryAttributes::from(ATTRIBUTES::AP::PrivAccess + ATTRIBUTES::XN::Enable);
+        // The devices have to fit between the base address and the end of the address space
+        let not_in_range = PageError::NotInRange {
+            addr: self.base_address.as_u32(),
+            start: self.base_address.as_u32(),
+            size: (u32::MAX - self.base_address.as_u32()).saturating_add(1),
+        };
+        let size = u32::try_from(self.device_base_addresses.len())
+            .ok()
+            .and_then(|count| count.checked_mul(0x0100_0000))
+            .ok_or(not_in_range)?;
+        if self.base_address.as_u32() as u64 + size as u64 > 1 << 32 {
+            return Err(not_in_range);
+        }
+        if !force {
+            if let Some(index) = base_table.find_overlap(self.base_address, size) {
+                return Err(PageError::AlreadyMapped { index });
+            }
+        }
         let mut base_addr = self.base_address;
         for addr in self.device_base_addresses.iter() {
             let tt_index = base_addr.translation_table_index();
             let device_base = PhysicalAddress::new((*addr as u32) << 24);
             // Each 16MB supersection uses 16 1MB sections
-            for index in 0..15 {
+            for index in 0..16 {
                 let section = TranslationTableDescriptor::new(
                     TranslationTableType::Section,
                     device_base + 0x10_0000 * index as u32,
@@ -295,8 +513,9 @@ impl DeviceVmemMapper {
                 )?;
                 base_table.table_mut()[tt_index + index as usize] = section;
             }
-            // increment the base address
-            base_addr += 0x0100_0000 as u32;
+            // increment the base address, this fails only after the last device at the end of the
+            // address space
+            base_addr.try_advance(0x0100_0000);
         }
         Ok(())
     }
@@ -316,6 +535,58 @@ impl DeviceVmemMapper {
             }
         }
     }
+    /// Access the register block of a device at the given physical address
+    ///
+    /// # Safety
+    /// The mapping has to be active and the device has to have a register block with the layout of
+    /// `T` at the given address.
+    pub unsafe fn mmio<T>(&self, phys_addr: PhysicalAddress) -> Option<Mmio<T>> {
+        self.lookup(phys_addr).map(|addr| Mmio::new(addr))
+    }
+}
+
+/// Translate MMIO address range to virtual addresses with a lookup table
+///
+/// This is the same as `DeviceVmemMapper`, but the lookup is a single array access instead of a
+/// search through the list of devices.
+pub struct IndexedDeviceVmemMapper {
+    mapper: DeviceVmemMapper,
+    // Index in the list of device base addresses for every leading byte of a physical address
+    index: [Option<u8>; 256],
+}
+
+impl IndexedDeviceVmemMapper {
+    /// Build the lookup table for a mapper
+    pub fn new(mapper: DeviceVmemMapper) -> Self {
+        let mut index = [None; 256];
+        // If a device is listed twice, the first entry wins as in DeviceVmemMapper::lookup
+        for (position, &addr) in mapper.device_base_addresses.iter().enumerate().rev() {
+            if position < 256 {
+                index[addr as usize] = Some(position as u8);
+            }
+        }
+        IndexedDeviceVmemMapper { mapper, index }
+    }
+    /// The underlying mapper
+    pub fn mapper(&self) -> &DeviceVmemMapper {
+        &self.mapper
+    }
+    /// Lookup virtual addresses from physical ones
+    #[inline(always)]
+    pub fn lookup(&self, phys_addr: PhysicalAddress) -> Option<VirtualAddress> {
+        let position = self.index[(phys_addr.as_u32() >> 24) as usize]?;
+        let naked_phys_addr = phys_addr.as_u32() & 0x00ff_ffff;
+        let out = self.mapper.base_address + ((position as u32) << 24);
+        Some(out | naked_phys_addr)
+    }
+}
+
+/// Allocator for the physical memory used by translation tables, page tables, and mapped frames
+pub trait FrameAllocator {
+    /// Allocate a block of physical memory of the given size, aligned to its size
+    fn allocate(&mut self, size: u32) -> Option<PhysicalAddress>;
+    /// Return a block of physical memory that was mapped or used as page table
+    fn deallocate(&mut self, addr: PhysicalAddress, size: u32);
 }
 
 /// Calculate the physical frame from a given virtual address
@@ -358,6 +629,62 @@ pub fn get_phys_addr(virt_addr: VirtualAddress) -> Result<PhysicalAddress> {
     Err(PageError::TranslationError)
 }
 
+/// Translate a virtual address with the given access and decode a failed translation
+fn translate(
+    virt_addr: VirtualAddress,
+    privileged: bool,
+    writable: bool,
+) -> Result<PhysicalAddress> {
+    let output = unsafe { get_phys_frame(virt_addr, privileged, writable) };
+    decode_par(output, virt_addr)
+}
+
+/// Decode the PAR after an address translation in the short descriptor format
+fn decode_par(par: u32, virt_addr: VirtualAddress) -> Result<PhysicalAddress> {
+    if (par & 0b1) == 0 {
+        return Ok(PhysicalAddress::new(
+            (par & !0xfff) | (virt_addr.as_u32() & 0xfff),
+        ));
+    }
+    // The fault status FS[4:0] is in bits 1 to 5, bit 6 is the external abort type
+    let status = (par >> 1) & 0x1f;
+    match status {
+        0b01001 | 0b01011 => Err(PageError::DomainError),
+        0b01101 | 0b01111 => Err(PageError::PermissionError),
+        _ => Err(PageError::TranslationError),
+    }
+}
+
+/// Check that `virt` currently translates to `expected_phys`
+///
+/// Uses the privileged read translation. Fails with the reason of a failed translation or with
+/// `PageError::MappingMismatch` if the address translates to a different physical address.
+pub fn verify_mapping(virt: VirtualAddress, expected_phys: PhysicalAddress) -> Result<()> {
+    verify_access(virt, expected_phys, true, false)
+}
+
+/// Check that `virt` translates to `expected_phys` for the given access
+///
+/// `privileged` selects a PL1 or an unprivileged access and `writable` a write or a read access.
+/// Fails with `PageError::PermissionError` or `PageError::DomainError` if the access is not
+/// allowed, otherwise like `verify_mapping`.
+pub fn verify_access(
+    virt: VirtualAddress,
+    expected_phys: PhysicalAddress,
+    privileged: bool,
+    writable: bool,
+) -> Result<()> {
+    let actual = translate(virt, privileged, writable)?;
+    if actual.as_u32() != expected_phys.as_u32() {
+        return Err(PageError::MappingMismatch {
+            virt: virt.as_u32(),
+            expected: expected_phys.as_u32(),
+            actual: actual.as_u32(),
+        });
+    }
+    Ok(())
+}
+
 #[derive(Copy, Clone, Debug)]
 /// Models an offset mapping between virtual and physical memory
 pub struct OffsetMapping {
@@ -375,6 +702,28 @@ impl OffsetMapping {
             size,
         }
     }
+    /// Create a new offset mapping where the virtual and the physical addresses coincide
+    pub const fn identity(start: VirtualAddress, size: u32) -> Self {
+        OffsetMapping {
+            virt_start: start,
+            phys_start: PhysicalAddress::new(start.0),
+            size,
+        }
+    }
+    /// Chain two offset mappings
+    ///
+    /// The physical addresses of `self` are interpreted as the virtual addresses of `other`.
+    /// Returns `None` if the output range of `self` is not contained in the input range of `other`.
+    pub fn compose(&self, other: &OffsetMapping) -> Option<OffsetMapping> {
+        let start = self.phys_start.as_u32();
+        let end = start.checked_add(self.size)?;
+        let other_end = other.virt_start.as_u32().checked_add(other.size)?;
+        if start < other.virt_start.as_u32() || end > other_end {
+            return None;
+        }
+        let phys_start = other.phys_start + (start - other.virt_start.as_u32());
+        Some(OffsetMapping::new(self.virt_start, phys_start, self.size))
+    }
     /// Checks whether a virtual address is in the range of the offset mapping
     pub fn virt_addr_in_range(&self, virt_addr: VirtualAddress) -> bool {
         if virt_addr.as_u32() < self.virt_start.as_u32() {
@@ -399,7 +748,11 @@ impl OffsetMapping {
     /// Given a offset mapping calculate the virtual address from the physical address
     pub fn convert_virt_addr(&self, vaddr: VirtualAddress) -> Result<PhysicalAddress> {
         if !self.virt_addr_in_range(vaddr) {
-            return Err(PageError::NotInRange);
+            return Err(PageError::NotInRange {
+                addr: vaddr.as_u32(),
+                start: self.virt_start.as_u32(),
+                size: self.size,
+            });
         }
         let diff = vaddr.as_u32() - self.virt_start.as_u32();
         Ok(PhysicalAddress::new(self.phys_start.as_u32() + diff))
@@ -408,7 +761,11 @@ impl OffsetMapping {
     /// Given a offset mapping calculate the physical address from the virtual address
     pub fn convert_phys_addr(&self, paddr: PhysicalAddress) -> Result<VirtualAddress> {
         if !self.phys_addr_in_range(paddr) {
-            return Err(PageError::NotInRange);
+            return Err(PageError::NotInRange {
+                addr: paddr.as_u32(),
+                start: self.phys_start.as_u32(),
+                size: self.size,
+            });
         }
         let diff = paddr.as_u32() - self.phys_start.as_u32();
         Ok(VirtualAddress::new(self.virt_start.as_u32() + diff))
@@ -430,6 +787,22 @@ pub enum TranslationTableType {
 }
 
 impl TranslationTableType {
+    /// Determine the type of a raw translation table entry
+    pub const fn from_raw(word: u32) -> Self {
+        // starts with
+        // 0b00: invalid
+        // 0b01: page
+        // 0b1x: section or supersection
+        // Bit 18 is 0 for section and 1 for supersection
+        match word & 0b11 {
+            0b00 => TranslationTableType::Invalid,
+            0b01 => TranslationTableType::Page,
+            _ => match word & 0x40000 {
+                0b0 => TranslationTableType::Section,
+                _ => TranslationTableType::Supersection,
+            },
+        }
+    }
     fn align(self) -> u32 {
         match self {
             TranslationTableType::Invalid => 0,
@@ -440,11 +813,19 @@ impl TranslationTableType {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 #[repr(transparent)]
 /// A descriptor for a translation table entry
 pub struct TranslationTableDescriptor(u32);
 
+impl fmt::Debug for TranslationTableDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TranslationTableDescriptor")
+            .field(&HexWord(self.0))
+            .finish()
+    }
+}
+
 impl fmt::LowerHex for TranslationTableDescriptor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let val = self.0;
@@ -463,12 +844,68 @@ impl ops::BitOrAssign<u32> for TranslationTableDescriptor {
         self.0 |= rhs;
     }
 }
+impl ops::BitAnd<u32> for TranslationTableDescriptor {
+    type Output = Self;
+    fn bitand(self, rhs: u32) -> Self {
+        TranslationTableDescriptor(self.0 & rhs)
+    }
+}
+impl ops::BitAndAssign<u32> for TranslationTableDescriptor {
+    fn bitand_assign(&mut self, rhs: u32) {
+        self.0 &= rhs;
+    }
+}
+impl ops::BitXor<u32> for TranslationTableDescriptor {
+    type Output = Self;
+    fn bitxor(self, rhs: u32) -> Self {
+        TranslationTableDescriptor(self.0 ^ rhs)
+    }
+}
+impl ops::BitXorAssign<u32> for TranslationTableDescriptor {
+    fn bitxor_assign(&mut self, rhs: u32) {
+        self.0 ^= rhs;
+    }
+}
 
 impl TranslationTableDescriptor {
     /// Create a new invalid table descriptor
     pub const fn new_empty() -> Self {
         Self(0)
     }
+    /// Create a new section descriptor in a constant context
+    ///
+    /// `flags` are the attribute bits in the layout of the section descriptor, the type bits are
+    /// set by this function.
+    /// # Safety
+    /// This function does not check whether the address is aligned to 1MB, ie `phys` has to be of
+    /// the form 0x???0_0000
+    pub const unsafe fn new_section_const(phys: PhysicalAddress, flags: u32) -> Self {
+        TranslationTableDescriptor(phys.0 | (flags & 0x000b_fdfd) | 0x2)
+    }
+    /// Create a new supersection descriptor for a physical address with up to 40 bits
+    ///
+    /// `flags` are the attribute bits in the layout of the supersection descriptor, the type bits
+    /// and the extended base address bits are set by this function. The address has to be aligned
+    /// to 16MB.
+    pub fn new_supersection(phys: PhysicalAddress64, flags: u32) -> Result<Self> {
+        let addr = phys.as_u64();
+        if addr & 0x00ff_ffff != 0 {
+            return Err(PageError::AlignError {
+                addr: addr as u32,
+                align: 0x0100_0000,
+            });
+        }
+        if addr >> 40 != 0 {
+            return Err(PageError::SizeError);
+        }
+        // PA[35:32] are stored in bits 20 to 23 and PA[39:36] in bits 5 to 8
+        let high = phys.high_bits();
+        let extended = ((high & 0xf) << 20) | ((high >> 4) << 5);
+        let base = addr as u32;
+        Ok(TranslationTableDescriptor(
+            base | extended | (flags & 0x000b_fc1d) | 0x4_0002,
+        ))
+    }
     /// Create a new table descriptor
     pub fn new(
         tabletype: TranslationTableType,
@@ -483,26 +920,347 @@ impl TranslationTableDescriptor {
         out |= addr.0;
         Ok(out)
     }
+    /// Clear all bits that are set in the mask
+    pub fn clear_field(&mut self, mask: u32) {
+        self.0 &= !mask;
+    }
+    /// Create a new table descriptor from attribute field values
+    pub fn new_typed(
+        tabletype: TranslationTableType,
+        addr: PhysicalAddress,
+        flags: FieldValue<u32, ATTRIBUTES::Register>,
+    ) -> Result<Self> {
+        Self::new(tabletype, addr, MemoryAttributes::from(flags))
+    }
     /// Determine the type of the table descriptor
     pub fn get_type(self) -> TranslationTableType {
-        // starts with
-        // 0b00: invalid
-        // 0b01: page
-        // 0b1x: section or supersection
-        // Bit 18 is 0 for section and 1 for supersection
-        match self.0 & 0b11 {
-            0b00 => TranslationTableType::Invalid,
-            0b01 => TranslationTableType::Page,
-            _ => match self.0 & 0x40000 {
-                0b0 => TranslationTableType::Section,
-                _ => TranslationTableType::Supersection,
-            },
+        TranslationTableType::from_raw(self.0)
+    }
+    /// Returns whether the entry is a valid translation or points to a page table
+    pub fn is_valid(self) -> bool {
+        self.get_type() != TranslationTableType::Invalid
+    }
+    /// Returns whether all bits of the entry are cleared, like for `new_empty`
+    ///
+    /// An invalid entry might still hold software defined bits and is not necessarily empty.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    /// Memory attributes of the entry, `None` for invalid entries
+    pub fn attributes(self) -> Option<MemoryAttributes> {
+        MemoryAttributes::from_table_descriptor(self)
+    }
+    /// Access permission bits AP[1:0] of a section or supersection
+    pub fn access_permission(self) -> Option<ATTRIBUTES::AP::Value> {
+        match self.get_type() {
+            TranslationTableType::Section | TranslationTableType::Supersection => {
+                self.attributes()?.access_permission()
+            }
+            _ => None,
+        }
+    }
+    /// Type extension bits of a section or supersection
+    pub fn tex(self) -> Option<u8> {
+        match self.get_type() {
+            TranslationTableType::Section | TranslationTableType::Supersection => {
+                self.attributes().map(MemoryAttributes::tex)
+            }
+            _ => None,
+        }
+    }
+    /// Domain of a section or page table entry
+    ///
+    /// Supersections and invalid entries have no domain.
+    pub fn domain(self) -> Option<u8> {
+        match self.get_type() {
+            TranslationTableType::Section | TranslationTableType::Page => {
+                self.attributes().map(MemoryAttributes::domain)
+            }
+            _ => None,
+        }
+    }
+    /// Check the reserved bits and encodings of the descriptor
+    ///
+    /// Returns `PageError::InvalidMemory` if a bit that should be zero is set, or if the access
+    /// permissions or the memory type use a reserved encoding. Invalid descriptors are always
+    /// well-formed, since the hardware ignores all other bits.
+    ///
+    /// `tex_remap` is the state of SCTLR.TRE the descriptor is used with, see
+    /// `tex_remap_enabled`. With TEX remapping the TEX bits are not checked, since TEX[2:1] are
+    /// available to the operating system and every index into the PRRR and NMRR is valid.
+    pub fn validate(&self, tex_remap: bool) -> Result<()> {
+        let val = self.0;
+        match self.get_type() {
+            TranslationTableType::Invalid => Ok(()),
+            TranslationTableType::Page => {
+                // Bit 4 should be zero
+                if val & 0x10 != 0 {
+                    return Err(PageError::InvalidMemory);
+                }
+                Ok(())
+            }
+            TranslationTableType::Section | TranslationTableType::Supersection => {
+                // AP2 = 1 with AP = 0b00 is reserved
+                if (val & 0x8000) != 0 && (val & 0xc00) == 0 {
+                    return Err(PageError::InvalidMemory);
+                }
+                if tex_remap {
+                    return Ok(());
+                }
+                let tex = (val >> 12) & 0b111;
+                let cb = (val >> 2) & 0b11;
+                match (tex, cb) {
+                    (0b001, 0b01) | (0b011, _) => Err(PageError::InvalidMemory),
+                    (0b010, cb) if cb != 0 => Err(PageError::InvalidMemory),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+    /// Get the physical base address the entry is pointing to.
+    ///
+    /// For supersections the extended base address bits are ignored.
+    pub fn get_addr(self) -> Result<PhysicalAddress> {
+        let mask = match self.get_type() {
+            TranslationTableType::Invalid => return Err(PageError::InvalidMemory),
+            TranslationTableType::Supersection => 0xff_ffff,
+            table_type => table_type.align(),
+        };
+        Ok(PhysicalAddress(self.0 & !mask))
+    }
+    /// Get the physical base address including the extended address bits of a supersection
+    pub fn get_addr64(self) -> Result<PhysicalAddress64> {
+        let base = PhysicalAddress64::from(self.get_addr()?);
+        if self.get_type() != TranslationTableType::Supersection {
+            return Ok(base);
+        }
+        let high = ((self.0 >> 20) & 0xf) | (((self.0 >> 5) & 0xf) << 4);
+        Ok(base + ((high as u64) << 32))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Translation table base registers
+pub enum Ttbr {
+    Ttbr0,
+    Ttbr1,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(transparent)]
+/// Attributes of the translation table walk as stored in the lower bits of the TTBR0
+pub struct Ttbr0Attributes(u32);
+
+/// Synchronization after writing a translation table base register
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SyncMode {
+    /// `isb`, so that the following instructions are translated with the new table
+    ///
+    /// The TLB is not invalidated. If the old table mapped addresses differently, the caller has
+    /// to invalidate the TLB.
+    Isb,
+    /// `isb` followed by invalidating the entire TLB, a `dsb` and another `isb`
+    IsbTlb,
+    /// Three `nop`s, which was the sequence used by earlier versions of this crate
+    ///
+    /// This does not guarantee that the new table is used by the following instructions.
+    Legacy,
+}
+
+impl SyncMode {
+    fn synchronize(self) {
+        match self {
+            SyncMode::Isb => asm::isb(),
+            SyncMode::IsbTlb => {
+                asm::isb();
+                TLBIALL.set(0);
+                asm::dsb();
+                asm::isb();
+            }
+            SyncMode::Legacy => {
+                asm::nop();
+                asm::nop();
+                asm::nop();
+            }
+        }
+    }
+}
+
+/// Address space identifier of non-global translations
+///
+/// ASID 0 is reserved for the switch between address spaces, see `context_switch`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Asid(u8);
+
+impl Asid {
+    /// Create a new ASID, returns `None` for the reserved ASID 0
+    pub const fn new(asid: u8) -> Option<Self> {
+        if asid == 0 {
+            None
+        } else {
+            Some(Asid(asid))
+        }
+    }
+    /// Converts the ASID to an unsigned integer
+    pub const fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+/// Switch to another address space
+///
+/// Uses the sequence of the ARM ARM for changing the ASID and the TTBR0 together:
+/// 1. `dsb`, so that all previous writes to the translation tables are visible,
+/// 2. set the reserved ASID 0 and `isb`,
+/// 3. write the ttbr0 and `isb`,
+/// 4. set the new ASID and `isb`.
+///
+/// Hence no translation of the new table is ever tagged with the old ASID and vice versa. The
+/// walk attributes of the current ttbr0 are kept. The reserved ASID 0 must not be used for
+/// non-global translations.
+///
+/// # Safety
+/// `new_ttbr0` has to point to a valid translation table that maps the code calling this
+/// function.
+pub unsafe fn context_switch(new_ttbr0: PhysicalAddress, new_asid: Asid) -> Result<()> {
+    new_ttbr0.check_align(0x3fff)?;
+    let attributes = TTBR0.get() & 0x7f;
+    asm::dsb();
+    CONTEXTIDR.modify(CONTEXTIDR::ASID.val(0));
+    asm::isb();
+    TTBR0.set(new_ttbr0.as_u32() | attributes);
+    asm::isb();
+    CONTEXTIDR.modify(CONTEXTIDR::ASID.val(new_asid.0 as u32));
+    asm::isb();
+    Ok(())
+}
+
+/// Cacheability of the memory accessed by the translation table walk
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CachePolicy {
+    NonCacheable,
+    WriteBackWriteAllocate,
+    WriteThrough,
+    WriteBackNoWriteAllocate,
+}
+
+impl CachePolicy {
+    fn bits(self) -> u32 {
+        match self {
+            CachePolicy::NonCacheable => 0b00,
+            CachePolicy::WriteBackWriteAllocate => 0b01,
+            CachePolicy::WriteThrough => 0b10,
+            CachePolicy::WriteBackNoWriteAllocate => 0b11,
         }
     }
 }
 
+impl Ttbr0Attributes {
+    /// Attributes for a non-cacheable, non-shareable walk
+    pub const fn new() -> Self {
+        Ttbr0Attributes(0)
+    }
+    /// Attributes for SMP systems: inner and outer write-back write-allocate, inner shareable
+    ///
+    /// The encoding is
+    /// * IRGN = 0b01, i.e. IRGN[1] (bit 0) is 0 and IRGN[0] (bit 6) is 1,
+    /// * RGN = 0b01 in bits 3 and 4,
+    /// * S (bit 1) and NOS (bit 5) are set.
+    ///
+    /// This is the same as
+    /// ```
+    ///     Ttbr0Attributes::new()
+    ///         .inner_cacheable(CachePolicy::WriteBackWriteAllocate)
+    ///         .outer_cacheable(CachePolicy::WriteBackWriteAllocate)
+    ///         .inner_shareable()
+    /// ```
+    pub const fn smp_cacheable() -> Self {
+        Ttbr0Attributes(SMP_CACHEABLE)
+    }
+    /// Set the inner cacheability of the walk
+    pub fn inner_cacheable(self, policy: CachePolicy) -> Self {
+        // IRGN is split: IRGN[1] is bit 0 and IRGN[0] is bit 6
+        let bits = policy.bits();
+        let mut val = self.0 & !0x41;
+        val |= (bits & 0b10) >> 1;
+        val |= (bits & 0b01) << 6;
+        Ttbr0Attributes(val)
+    }
+    /// Set the outer cacheability of the walk
+    pub fn outer_cacheable(self, policy: CachePolicy) -> Self {
+        Ttbr0Attributes((self.0 & !0x18) | (policy.bits() << 3))
+    }
+    /// Mark the translation tables as outer shareable
+    pub fn shareable(self) -> Self {
+        Ttbr0Attributes((self.0 | 0x2) & !0x20)
+    }
+    /// Mark the translation tables as inner shareable
+    pub fn inner_shareable(self) -> Self {
+        Ttbr0Attributes(self.0 | 0x22)
+    }
+    /// Raw value of the attribute bits
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+const SMP_CACHEABLE: u32 = (1 << 6) | (0b01 << 3) | (1 << 5) | (1 << 1);
+// Checked against the ARM ARM encoding, fails to compile if the value changes
+const _: [(); 0x6a] = [(); SMP_CACHEABLE as usize];
+
+impl From<FieldValue<u32, TTBR::Register>> for Ttbr0Attributes {
+    fn from(attributes: FieldValue<u32, TTBR::Register>) -> Self {
+        Ttbr0Attributes(u32::from(attributes) & 0x7f)
+    }
+}
+
+impl Default for Ttbr0Attributes {
+    fn default() -> Self {
+        Ttbr0Attributes(0)
+    }
+}
+
+pub const SECTION_SIZE: u32 = 0x10_0000;
+pub const PAGE_SIZE: u32 = 0x1000;
+
 pub const TRANSLATION_TABLE_SIZE: usize = 4096; // each section is 1MB big; 4GB / 1MB is 4096
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Index into a translation table that is guaranteed to be in range
+pub struct TableIndex(usize);
+
+impl TableIndex {
+    /// Create a new index, returns `None` if the index is out of range
+    pub fn new(index: usize) -> Option<Self> {
+        if index < TRANSLATION_TABLE_SIZE {
+            Some(TableIndex(index))
+        } else {
+            None
+        }
+    }
+    /// Converts the index to an unsigned integer
+    pub fn as_usize(self) -> usize {
+        self.0
+    }
+}
+
+impl TryFrom<usize> for TableIndex {
+    type Error = PageError;
+    fn try_from(index: usize) -> Result<Self> {
+        TableIndex::new(index).ok_or(PageError::IndexError {
+            index,
+            size: TRANSLATION_TABLE_SIZE,
+        })
+    }
+}
+
+impl From<VirtualAddress> for TableIndex {
+    fn from(addr: VirtualAddress) -> Self {
+        // The address has 32 bits, so the index is always smaller than 4096
+        TableIndex(addr.translation_table_index())
+    }
+}
+
 #[repr(C)]
 #[repr(align(16384))]
 /// Base level translation table memory
@@ -516,6 +1274,50 @@ impl TranslationTableMemory {
             table: [TranslationTableDescriptor::new_empty(); TRANSLATION_TABLE_SIZE],
         }
     }
+    /// View the table as raw bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, mem::size_of::<Self>()) }
+    }
+    /// View the table as mutable raw bytes
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self as *mut Self as *mut u8, mem::size_of::<Self>()) }
+    }
+    /// Reinterpret a byte slice as table memory
+    ///
+    /// The slice has to be exactly 16384 bytes long and aligned to 16384 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<&Self> {
+        if bytes.len() != mem::size_of::<Self>() {
+            return Err(PageError::SizeError);
+        }
+        VirtualAddress::from_ptr(bytes.as_ptr()).check_align(mem::align_of::<Self>() as u32 - 1)?;
+        Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
+    }
+}
+
+// The translation table has to be exactly 16KB big and aligned to 16KB. These fail to compile if
+// the size or the alignment changes.
+const _: [(); TRANSLATION_TABLE_SIZE * 4] = [(); mem::size_of::<TranslationTableMemory>()];
+const _: [(); 16384] = [(); mem::align_of::<TranslationTableMemory>()];
+
+impl Default for TranslationTableMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Callbacks for `TranslationTable::walk`
+///
+/// All callbacks do nothing by default.
+pub trait TableVisitor {
+    /// Called for sections and supersections in the translation table
+    fn section(&mut self, _index: usize, _descriptor: TranslationTableDescriptor) {}
+    /// Called for an entry in the translation table pointing to a page table, before the entries
+    /// of the page table are visited
+    fn page_table(&mut self, _index: usize, _descriptor: TranslationTableDescriptor) {}
+    /// Called for small and large pages in a page table
+    fn page(&mut self, _l1_index: usize, _l2_index: usize, _descriptor: PageTableDescriptor) {}
+    /// Called for invalid entries, `l2_index` is `None` for entries of the translation table
+    fn invalid(&mut self, _l1_index: usize, _l2_index: Option<usize>) {}
 }
 
 #[derive(Debug)]
@@ -529,23 +1331,101 @@ impl TranslationTable {
     pub const fn new(pointer: *mut TranslationTableMemory) -> Self {
         Self { pointer }
     }
+    /// Create a base table from a reference to static memory
+    ///
+    /// Prefer this over `new` if the memory is a `static mut`, since the reference guarantees
+    /// that the memory is valid for the lifetime of the table.
+    pub fn from_static(mem: &'static mut TranslationTableMemory) -> Self {
+        Self { pointer: mem }
+    }
+    /// Create a pointer to a base table and clear all entries of the table
+    ///
+    /// # Safety
+    /// The caller must garantee that the pointer points to valid memory that is not used
+    /// otherwise.
+    pub unsafe fn new_zeroed(pointer: *mut TranslationTableMemory) -> Self {
+        for entry in (*pointer).table.iter_mut() {
+            ptr::write_volatile(entry, TranslationTableDescriptor::new_empty());
+        }
+        Self { pointer }
+    }
     /// Registers the translation page table as ttbr0
     ///
+    /// Same as `set_as_ttbr0_synced` with `SyncMode::IsbTlb`.
+    ///
     /// # Safety
     /// The caller must garantee that the new translation page table is valid
     pub unsafe fn set_as_ttbr0(&self) -> Result<()> {
-        let virt_addr = VirtualAddress::from_ptr(self.pointer);
-        let phys_addr = get_phys_addr(virt_addr)?;
+        self.set_as_ttbr0_synced(SyncMode::IsbTlb)
+    }
+
+    /// Registers the translation page table as ttbr0 with the given synchronization
+    ///
+    /// The sequence is
+    /// 1. `dsb`, so that all previous writes to the translation tables are visible to the
+    ///    table walk,
+    /// 2. write the ttbr0,
+    /// 3. the synchronization selected by `sync`.
+    ///
+    /// Fails with `PageError::AlignError` before the write if the physical address of the table is
+    /// not aligned to 16KB. The ttbr0 is read back afterwards as a sanity check.
+    ///
+    /// # Safety
+    /// The caller must garantee that the new translation page table is valid
+    pub unsafe fn set_as_ttbr0_synced(&self, sync: SyncMode) -> Result<()> {
+        let phys_addr = self.phys_addr()?;
+        // The low bits of the TTBR0 are attributes, a misaligned table would be truncated
+        phys_addr.check_align(0x3fff)?;
+        asm::dsb();
         TTBR0.set(phys_addr.as_u32());
-        arm::__nop();
-        arm::__nop();
-        arm::__nop();
-        Ok(())
+        sync.synchronize();
+        Self::verify_ttbr0(phys_addr)
+    }
+
+    /// Registers the translation page table as ttbr0 with the given walk attributes
+    ///
+    /// Uses the same sequence as `set_as_ttbr0_synced` with `SyncMode::Isb`.
+    ///
+    /// # Safety
+    /// The caller must garantee that the new translation page table is valid
+    pub unsafe fn set_as_ttbr0_with_attrs(&self, attributes: Ttbr0Attributes) -> Result<()> {
+        let phys_addr = self.phys_addr()?;
+        // The low bits of the TTBR0 are attributes, a misaligned table would be truncated
+        phys_addr.check_align(0x3fff)?;
+        asm::dsb();
+        TTBR0.set(phys_addr.as_u32() | attributes.0);
+        asm::isb();
+        Self::verify_ttbr0(phys_addr)
+    }
+
+    /// Physical address of the translation table memory
+    ///
+    /// Uses the current address translation to resolve the address of the backing memory.
+    pub fn phys_addr(&self) -> Result<PhysicalAddress> {
+        get_phys_addr(VirtualAddress::from_ptr(self.pointer))
+    }
+
+    /// Checks that the base address in the ttbr0 matches the expected address
+    ///
+    /// The check fails if the translation table is not aligned to 16KB.
+    fn verify_ttbr0(phys_addr: PhysicalAddress) -> Result<()> {
+        let current = Self::get_ttbr0_phys();
+        if current.as_u32() == phys_addr.as_u32() {
+            Ok(())
+        } else {
+            Err(PageError::RegisterMismatch)
+        }
+    }
+
+    /// Returns the walk attributes of the current ttbr0
+    pub fn ttbr0_attributes() -> Ttbr0Attributes {
+        Ttbr0Attributes(TTBR0.get() & 0x7f)
     }
 
     /// This functions is deprecated since it assumes that the ttbr0 is on indentity-mapped memory
     /// address
     /// Resolution: Use get_phys_addr() and convert it to a virtual memory address
+    #[deprecated(note = "use get_ttbr0(offset_mapping) instead")]
     pub fn get_current_ttbr0() -> Self {
         let ttbr0 = TTBR0.get() & !0x3fff;
         TranslationTable {
@@ -553,12 +1433,25 @@ impl TranslationTable {
         }
     }
     /// Get the ttbr0 translation table
+    ///
+    /// The offset mapping has to cover the whole translation table, otherwise
+    /// `PageError::NotInRange` is returned.
     pub fn get_ttbr0(offset_mapping: OffsetMapping) -> Result<Self> {
-        let phys_addr = Self::get_ttbr0_phys();
-        let virt_addr = offset_mapping.convert_phys_addr(phys_addr)?;
+        let virt_addr = Self::table_virt_addr(offset_mapping, Self::get_ttbr0_phys())?;
         Ok(Self::new(virt_addr.as_mut_ptr()))
     }
 
+    // Virtual address of the table memory at `phys_addr`, the whole table has to be covered
+    fn table_virt_addr(
+        offset_mapping: OffsetMapping,
+        phys_addr: PhysicalAddress,
+    ) -> Result<VirtualAddress> {
+        let virt_addr = offset_mapping.convert_phys_addr(phys_addr)?;
+        let last_addr = phys_addr + (mem::size_of::<TranslationTableMemory>() as u32 - 1);
+        offset_mapping.convert_phys_addr(last_addr)?;
+        Ok(virt_addr)
+    }
+
     /// Returns the physical address of the translation page table
     pub fn get_ttbr0_phys() -> PhysicalAddress {
         PhysicalAddress::new(TTBR0.get() & !0x3fff)
@@ -580,6 +1473,446 @@ impl TranslationTable {
     pub fn table(&self) -> &[TranslationTableDescriptor; TRANSLATION_TABLE_SIZE] {
         unsafe { &(*self.pointer).table }
     }
+
+    /// Clear all entries of the translation table and free the page tables
+    ///
+    /// Every entry is cleared and the TLB is invalidated before the memory it pointed to is handed
+    /// to the allocator, hence the walker never sees freed memory. If `free_leaves` is given the
+    /// mapped frames, sections, and supersections are freed as well; the offset mapping is used to
+    /// access the page tables.
+    ///
+    /// # Safety
+    /// The translation table must not be used to translate the code calling this function and all
+    /// page tables must have been allocated from `alloc`.
+    pub unsafe fn destroy<A: FrameAllocator>(
+        &mut self,
+        alloc: &mut A,
+        free_leaves: Option<OffsetMapping>,
+    ) -> Result<()> {
+        for index in 0..TRANSLATION_TABLE_SIZE {
+            let descriptor = self.table()[index];
+            let table_type = descriptor.get_type();
+            if table_type == TranslationTableType::Invalid {
+                continue;
+            }
+            self.table_mut()[index] = TranslationTableDescriptor::new_empty();
+            asm::dsb();
+            TLBIALL.set(0);
+            asm::dsb();
+            asm::isb();
+            let addr = descriptor.get_addr()?;
+            match (table_type, free_leaves) {
+                (TranslationTableType::Page, Some(mapping)) => {
+                    let page_table: *const PageTableMemory =
+                        mapping.convert_phys_addr(addr)?.as_ptr();
+                    for (page_index, page) in (*page_table).table.iter().enumerate() {
+                        match page.get_type() {
+                            PageTableType::Invalid => {}
+                            PageTableType::SmallPage => alloc.deallocate(page.get_addr()?, 0x1000),
+                            // Large pages are repeated in 16 consecutive entries
+                            PageTableType::LargePage => {
+                                if page_index % 16 == 0 {
+                                    alloc.deallocate(page.get_addr()?, 0x1_0000);
+                                }
+                            }
+                        }
+                    }
+                    alloc.deallocate(addr, mem::size_of::<PageTableMemory>() as u32);
+                }
+                (TranslationTableType::Page, None) => {
+                    alloc.deallocate(addr, mem::size_of::<PageTableMemory>() as u32);
+                }
+                (TranslationTableType::Section, Some(_)) => alloc.deallocate(addr, 0x10_0000),
+                // Supersections are repeated in 16 consecutive entries
+                (TranslationTableType::Supersection, Some(_)) => {
+                    if index % 16 == 0 {
+                        alloc.deallocate(addr, 0x100_0000);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the entries of another translation table starting at `start_index`
+    ///
+    /// The copy is shallow: entries pointing to page tables are copied as they are, hence both
+    /// translation tables share the page tables afterwards. Use `deep_copy_from` to duplicate the
+    /// page tables as well.
+    ///
+    /// # Safety
+    /// The caller must ensure that the copied entries don't interfere with the memory layout of
+    /// the program
+    pub unsafe fn copy_from(&mut self, src: &TranslationTable, start_index: usize) -> Result<()> {
+        let entries = self.check_start_index(start_index)?;
+        entries.copy_from_slice(&src.table()[start_index..]);
+        Ok(())
+    }
+
+    /// Copy the entries of another translation table and duplicate its page tables
+    ///
+    /// New page tables are requested from the allocator and accessed with the offset mapping.
+    ///
+    /// # Safety
+    /// Same as `copy_from`
+    pub unsafe fn deep_copy_from<A: FrameAllocator>(
+        &mut self,
+        src: &TranslationTable,
+        start_index: usize,
+        alloc: &mut A,
+        offset_mapping: OffsetMapping,
+    ) -> Result<()> {
+        self.check_start_index(start_index)?;
+        for index in start_index..TRANSLATION_TABLE_SIZE {
+            let descriptor = src.table()[index];
+            if descriptor.get_type() != TranslationTableType::Page {
+                self.table_mut()[index] = descriptor;
+                continue;
+            }
+            let size = mem::size_of::<PageTableMemory>() as u32;
+            let src_phys = descriptor.get_addr()?;
+            let dst_phys = alloc.allocate(size).ok_or(PageError::InvalidMemory)?;
+            dst_phys.check_align(size - 1)?;
+            let src_table: *const PageTableMemory =
+                offset_mapping.convert_phys_addr(src_phys)?.as_ptr();
+            let dst_table: *mut PageTableMemory =
+                offset_mapping.convert_phys_addr(dst_phys)?.as_mut_ptr();
+            ptr::copy_nonoverlapping(src_table, dst_table, 1);
+            // Keep the attributes of the entry and replace the base address
+            self.table_mut()[index] =
+                TranslationTableDescriptor((descriptor.0 & 0x3ff) | dst_phys.as_u32());
+        }
+        Ok(())
+    }
+
+    fn check_start_index(
+        &mut self,
+        start_index: usize,
+    ) -> Result<&mut [TranslationTableDescriptor]> {
+        if start_index >= TRANSLATION_TABLE_SIZE {
+            return Err(PageError::IndexError {
+                index: start_index,
+                size: TRANSLATION_TABLE_SIZE,
+            });
+        }
+        Ok(unsafe { &mut self.table_mut()[start_index..] })
+    }
+
+    /// Traverse the translation table and all page tables
+    ///
+    /// The visitor is called for every entry in order of the virtual addresses. The page tables
+    /// are accessed with the offset mapping, if a page table is not covered by the mapping the walk
+    /// stops with `PageError::NotInRange`.
+    pub fn walk<V: TableVisitor>(
+        &self,
+        visitor: &mut V,
+        offset_mapping: OffsetMapping,
+    ) -> Result<()> {
+        for (index, descriptor) in self.table().iter().enumerate() {
+            match descriptor.get_type() {
+                TranslationTableType::Invalid => visitor.invalid(index, None),
+                TranslationTableType::Section | TranslationTableType::Supersection => {
+                    visitor.section(index, *descriptor)
+                }
+                TranslationTableType::Page => {
+                    visitor.page_table(index, *descriptor);
+                    let phys_addr = descriptor.get_addr()?;
+                    let page_table: *const PageTableMemory =
+                        offset_mapping.convert_phys_addr(phys_addr)?.as_ptr();
+                    let pages = unsafe { &(*page_table).table };
+                    for (page_index, page) in pages.iter().enumerate() {
+                        match page.get_type() {
+                            PageTableType::Invalid => visitor.invalid(index, Some(page_index)),
+                            _ => visitor.page(index, page_index, *page),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Count the entries of the translation table by type
+    pub fn stats(&self) -> TableStats {
+        let mut stats = TableStats::default();
+        for descriptor in self.table().iter() {
+            match descriptor.get_type() {
+                TranslationTableType::Invalid => stats.invalid += 1,
+                TranslationTableType::Page => stats.page_tables += 1,
+                TranslationTableType::Section => stats.sections += 1,
+                TranslationTableType::Supersection => stats.supersections += 1,
+            }
+        }
+        stats
+    }
+
+    /// Bitmask of the domains referenced by the valid entries of the translation table
+    ///
+    /// Bit `n` is set if domain `n` is used. Supersections always belong to domain 0.
+    pub fn domains_used(&self) -> u16 {
+        let mut domains = 0;
+        for descriptor in self.table().iter() {
+            let domain = match descriptor.get_type() {
+                TranslationTableType::Invalid => continue,
+                TranslationTableType::Supersection => 0,
+                TranslationTableType::Page | TranslationTableType::Section => {
+                    (descriptor.0 >> 5) & 0xf
+                }
+            };
+            domains |= 1 << domain;
+        }
+        domains
+    }
+
+    /// Iterator over the domains referenced by the valid entries of the translation table
+    pub fn domains_used_iter(&self) -> BitMaskIter {
+        BitMaskIter::from(self.domains_used())
+    }
+
+    /// Make the translation table accessible through a fixed virtual window
+    ///
+    /// With the short descriptor format a first level entry can not point at the translation
+    /// table itself, since first level descriptors are not valid second level descriptors.
+    /// Instead `page_table` is cleared and registered at `slot` and its first four small pages map
+    /// the 16KB of the translation table. The remaining pages of the window are left unmapped.
+    ///
+    /// Returns the base of the 1MB window covered by `slot`. Use `recursive_entry_addr` to
+    /// compute the virtual address of an entry in the window.
+    ///
+    /// Fails with `PageError::AlreadyMapped` if `slot` already holds a valid entry. Since the
+    /// slot was unmapped before, the TLB holds no entries for the window and no invalidation is
+    /// needed, only a `dsb` and an `isb` after the window is written.
+    ///
+    /// # Safety
+    /// Same as `table_mut`, additionally `page_table` has to be valid memory that is not used
+    /// otherwise.
+    pub unsafe fn install_recursive(
+        &mut self,
+        slot: TableIndex,
+        page_table: *mut PageTableMemory,
+        attributes: MemoryAttributes,
+    ) -> Result<VirtualAddress> {
+        if self.table()[slot.0].is_valid() {
+            return Err(PageError::AlreadyMapped { index: slot.0 });
+        }
+        let phys_addr = self.phys_addr()?;
+        phys_addr.check_align(0x3fff)?;
+        let mut window = PageTable::new_zeroed(page_table, attributes, self, slot.0)?;
+        let base = VirtualAddress::new((slot.0 as u32) << 20);
+        let size = mem::size_of::<TranslationTableMemory>() as u32;
+        for (page, offset) in (0..size).step_by(PAGE_SIZE as usize).enumerate() {
+            window.table_mut()[page] =
+                PageTableDescriptor::new(PageTableType::SmallPage, phys_addr + offset, attributes)?;
+        }
+        asm::dsb();
+        asm::isb();
+        Ok(base)
+    }
+
+    /// Virtual address of the entry `index` in the window returned by `install_recursive`
+    pub fn recursive_entry_addr(window: VirtualAddress, index: TableIndex) -> VirtualAddress {
+        window + (index.0 * mem::size_of::<TranslationTableDescriptor>()) as u32
+    }
+
+    /// Virtual address of the entry translating `virt` in the window returned by
+    /// `install_recursive`
+    pub fn recursive_entry_addr_for(
+        window: VirtualAddress,
+        virt: VirtualAddress,
+    ) -> VirtualAddress {
+        Self::recursive_entry_addr(window, TableIndex::from(virt))
+    }
+
+    /// Start writing entries with automatic TLB maintenance
+    ///
+    /// See `MappingTx` for details.
+    pub fn begin_mapping(&mut self) -> MappingTx<'_> {
+        MappingTx {
+            table: self,
+            touched: [0; MAPPING_TX_ENTRIES],
+            count: 0,
+            invalidate_all: false,
+        }
+    }
+
+    /// Map a 16MB supersection at `base_index`
+    ///
+    /// A supersection has to be written to 16 consecutive entries starting at an index that is a
+    /// multiple of 16. `flags` are the attribute bits in the layout of the supersection
+    /// descriptor, see `TranslationTableDescriptor::new_supersection`.
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn map_supersection<P: Into<PhysicalAddress64>>(
+        &mut self,
+        base_index: usize,
+        phys: P,
+        flags: u32,
+    ) -> Result<()> {
+        if base_index + 16 > TRANSLATION_TABLE_SIZE {
+            return Err(PageError::IndexError {
+                index: base_index,
+                size: TRANSLATION_TABLE_SIZE,
+            });
+        }
+        if base_index % 16 != 0 {
+            return Err(PageError::AlignError {
+                addr: (base_index as u32) << 20,
+                align: 0x0100_0000,
+            });
+        }
+        let descriptor = TranslationTableDescriptor::new_supersection(phys.into(), flags)?;
+        for entry in self.table_mut()[base_index..base_index + 16].iter_mut() {
+            *entry = descriptor;
+        }
+        Ok(())
+    }
+
+    /// Find the first valid entry that translates an address in `virt..virt + size`
+    ///
+    /// Returns the index of the conflicting entry in the translation table or `None` if the range
+    /// is unmapped. Use this to avoid silently overwriting a live mapping.
+    pub fn find_overlap(&self, virt: VirtualAddress, size: u32) -> Option<usize> {
+        if size == 0 {
+            return None;
+        }
+        let start = virt.translation_table_index();
+        let end = (virt.as_u32() as u64 + size as u64 - 1) >> 20;
+        let end = cmp::min(end as usize, TRANSLATION_TABLE_SIZE - 1);
+        (start..=end).find(|&index| self.table()[index].is_valid())
+    }
+
+    /// Install `descriptor` at `index`
+    ///
+    /// Fails with `PageError::AlreadyMapped` if the entry is already valid, unless `force` is set.
+    /// The caller is responsible for the TLB maintenance when replacing a live entry.
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn map_entry(
+        &mut self,
+        index: TableIndex,
+        descriptor: TranslationTableDescriptor,
+        force: bool,
+    ) -> Result<()> {
+        if !force && self.entry(index).is_valid() {
+            return Err(PageError::AlreadyMapped { index: index.0 });
+        }
+        *self.entry_mut(index) = descriptor;
+        Ok(())
+    }
+
+    /// Mutable reference to a single entry of the translation table
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn entry_mut(&mut self, index: TableIndex) -> &mut TranslationTableDescriptor {
+        &mut (*self.pointer).table[index.0]
+    }
+
+    /// Immutable reference to a single entry of the translation table
+    pub fn entry(&self, index: TableIndex) -> &TranslationTableDescriptor {
+        &self.table()[index.0]
+    }
+
+    /// Reference to the entry at `index` or `None` if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<&TranslationTableDescriptor> {
+        self.table().get(index)
+    }
+
+    /// Mutable reference to the entry at `index` or `None` if it is out of bounds
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn get_mut(&mut self, index: usize) -> Option<&mut TranslationTableDescriptor> {
+        self.table_mut().get_mut(index)
+    }
+}
+
+/// Read-only indexing, panics if the index is out of bounds
+///
+/// There is no `IndexMut` implementation since writing to the translation table is unsafe, use
+/// `get_mut` or `entry_mut` instead.
+impl ops::Index<usize> for TranslationTable {
+    type Output = TranslationTableDescriptor;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.table()[index]
+    }
+}
+
+/// Number of entries of a translation table by type
+///
+/// Every entry maps 1MB, hence a supersection is counted as 16 entries.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TableStats {
+    pub sections: usize,
+    pub supersections: usize,
+    pub page_tables: usize,
+    pub invalid: usize,
+}
+
+/// Number of entries a `MappingTx` invalidates one by one before invalidating the entire TLB
+const MAPPING_TX_ENTRIES: usize = 8;
+
+/// Writes to a translation table followed by the required TLB maintenance
+///
+/// The transaction records the entries replaced by `set`. On `commit` or drop the TLB entries of
+/// the replaced translations are invalidated by address, followed by a `dsb` and an `isb`. If
+/// more than a few entries have been replaced or an entry pointing to a page table has been
+/// replaced, the entire TLB is invalidated instead. Invalid entries are not held in the TLB, so
+/// writing to them does not require any invalidation.
+///
+/// The invalidation by address uses `TLBIMVAA`, which requires the Multiprocessing Extensions.
+/// On cores without them, e.g. the Cortex-A8, the entire TLB is invalidated instead.
+pub struct MappingTx<'a> {
+    table: &'a mut TranslationTable,
+    touched: [usize; MAPPING_TX_ENTRIES],
+    count: usize,
+    invalidate_all: bool,
+}
+
+impl<'a> MappingTx<'a> {
+    /// Write `descriptor` to the entry at `index`
+    ///
+    /// # Safety
+    /// Same as `TranslationTable::table_mut`
+    pub unsafe fn set(&mut self, index: TableIndex, descriptor: TranslationTableDescriptor) {
+        let old = *self.table.entry(index);
+        *self.table.entry_mut(index) = descriptor;
+        match old.get_type() {
+            TranslationTableType::Invalid => {}
+            // The TLB might hold any of the 256 pages of the page table
+            TranslationTableType::Page => self.invalidate_all = true,
+            TranslationTableType::Section | TranslationTableType::Supersection => {
+                if self.count < MAPPING_TX_ENTRIES {
+                    self.touched[self.count] = index.0;
+                    self.count += 1;
+                } else {
+                    self.invalidate_all = true;
+                }
+            }
+        }
+    }
+
+    /// Finish the transaction and perform the TLB maintenance
+    pub fn commit(self) {}
+}
+
+impl<'a> Drop for MappingTx<'a> {
+    fn drop(&mut self) {
+        asm::dsb();
+        if self.invalidate_all || !has_multiprocessing_extensions() {
+            TLBIALL.set(0);
+        } else {
+            for index in self.touched[..self.count].iter() {
+                TLBIMVAA.set((*index as u32) << 20);
+            }
+        }
+        asm::dsb();
+        asm::isb();
+    }
 }
 
 impl fmt::LowerHex for TranslationTable {
@@ -589,6 +1922,79 @@ impl fmt::LowerHex for TranslationTable {
     }
 }
 
+/// Translation table format, abstracting over the short and the long descriptor format
+///
+/// Code that is generic over `P: Paging` works with both formats. Only the short descriptor
+/// format is implemented, see `ShortDescriptor`.
+pub trait Paging {
+    /// Entry of the first level translation table
+    type Descriptor: Copy;
+    /// Memory attributes of a mapping
+    type Attributes: Copy;
+    /// First level translation table
+    type Table;
+    /// Size of a block mapped by a single first level entry in bytes
+    const BLOCK_SIZE: u32;
+
+    /// Create a descriptor mapping a block at `phys`
+    fn block_descriptor(
+        phys: PhysicalAddress,
+        attributes: Self::Attributes,
+    ) -> Result<Self::Descriptor>;
+    /// Returns whether the descriptor is a valid translation
+    fn is_valid(descriptor: Self::Descriptor) -> bool;
+    /// Output address of the descriptor
+    fn descriptor_addr(descriptor: Self::Descriptor) -> Result<PhysicalAddress>;
+    /// Install the block `descriptor` for `virt`
+    ///
+    /// Fails with `PageError::AlreadyMapped` if the entry is already valid, unless `force` is set.
+    ///
+    /// # Safety
+    /// The caller is responsible for the TLB maintenance when replacing a live entry.
+    unsafe fn map_block(
+        table: &mut Self::Table,
+        virt: VirtualAddress,
+        descriptor: Self::Descriptor,
+        force: bool,
+    ) -> Result<()>;
+    /// Translate an address with the current translation regime
+    fn translate(virt: VirtualAddress) -> Result<PhysicalAddress> {
+        get_phys_addr(virt)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Short descriptor translation table format with 1MB sections as blocks
+pub struct ShortDescriptor;
+
+impl Paging for ShortDescriptor {
+    type Descriptor = TranslationTableDescriptor;
+    type Attributes = MemoryAttributes;
+    type Table = TranslationTable;
+    const BLOCK_SIZE: u32 = 0x10_0000;
+
+    fn block_descriptor(
+        phys: PhysicalAddress,
+        attributes: MemoryAttributes,
+    ) -> Result<Self::Descriptor> {
+        TranslationTableDescriptor::new(TranslationTableType::Section, phys, attributes)
+    }
+    fn is_valid(descriptor: Self::Descriptor) -> bool {
+        descriptor.is_valid()
+    }
+    fn descriptor_addr(descriptor: Self::Descriptor) -> Result<PhysicalAddress> {
+        descriptor.get_addr()
+    }
+    unsafe fn map_block(
+        table: &mut TranslationTable,
+        virt: VirtualAddress,
+        descriptor: Self::Descriptor,
+        force: bool,
+    ) -> Result<()> {
+        table.map_entry(TableIndex::from(virt), descriptor, force)
+    }
+}
+
 //
 // Page table stuff
 //
@@ -603,6 +2009,18 @@ pub enum PageTableType {
 }
 
 impl PageTableType {
+    /// Determine the type of a raw page table entry
+    pub const fn from_raw(word: u32) -> Self {
+        // starts with
+        // 0b00 Invalid
+        // 0b01 LargePage
+        // 0b1x SmallPage
+        match word & 0b11 {
+            0b00 => PageTableType::Invalid,
+            0b01 => PageTableType::LargePage,
+            _ => PageTableType::SmallPage,
+        }
+    }
     fn align(self) -> u32 {
         match self {
             PageTableType::Invalid => 0,
@@ -612,11 +2030,19 @@ impl PageTableType {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 #[repr(transparent)]
 /// A descriptor for a page table entry
 pub struct PageTableDescriptor(u32);
 
+impl fmt::Debug for PageTableDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PageTableDescriptor")
+            .field(&HexWord(self.0))
+            .finish()
+    }
+}
+
 impl fmt::LowerHex for PageTableDescriptor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let val = self.0;
@@ -635,12 +2061,44 @@ impl ops::BitOrAssign<u32> for PageTableDescriptor {
         self.0 |= rhs;
     }
 }
+impl ops::BitAnd<u32> for PageTableDescriptor {
+    type Output = Self;
+    fn bitand(self, rhs: u32) -> Self {
+        PageTableDescriptor(self.0 & rhs)
+    }
+}
+impl ops::BitAndAssign<u32> for PageTableDescriptor {
+    fn bitand_assign(&mut self, rhs: u32) {
+        self.0 &= rhs;
+    }
+}
+impl ops::BitXor<u32> for PageTableDescriptor {
+    type Output = Self;
+    fn bitxor(self, rhs: u32) -> Self {
+        PageTableDescriptor(self.0 ^ rhs)
+    }
+}
+impl ops::BitXorAssign<u32> for PageTableDescriptor {
+    fn bitxor_assign(&mut self, rhs: u32) {
+        self.0 ^= rhs;
+    }
+}
 
 impl PageTableDescriptor {
     /// Construct a new invalid page descriptor
     pub const fn new_empty() -> PageTableDescriptor {
         PageTableDescriptor(0)
     }
+    /// Construct a new small page descriptor in a constant context
+    ///
+    /// `flags` are the attribute bits in the layout of the small page descriptor, the type bit is
+    /// set by this function.
+    /// # Safety
+    /// This function does not check whether the address is aligned to 4KB, ie `phys` has to be of
+    /// the form 0x????_?000
+    pub const unsafe fn new_small_page_const(phys: PhysicalAddress, flags: u32) -> Self {
+        PageTableDescriptor(phys.0 | (flags & 0xffd) | 0x2)
+    }
     /// Construct a new page descriptor
     pub fn new(
         pagetype: PageTableType,
@@ -655,17 +2113,49 @@ impl PageTableDescriptor {
         out |= addr.0;
         Ok(out)
     }
+    /// Clear all bits that are set in the mask
+    pub fn clear_field(&mut self, mask: u32) {
+        self.0 &= !mask;
+    }
+    /// Construct a new page descriptor from attribute field values
+    pub fn new_typed(
+        pagetype: PageTableType,
+        addr: PhysicalAddress,
+        flags: FieldValue<u32, ATTRIBUTES::Register>,
+    ) -> Result<PageTableDescriptor> {
+        Self::new(pagetype, addr, MemoryAttributes::from(flags))
+    }
     /// Determine the type of the page descriptor
     pub fn get_type(self) -> PageTableType {
-        // starts with
-        // 0b00 Invalid
-        // 0b01 LargePage
-        // 0b1x SmallPage
-        match self.0 & 0b11 {
-            0b00 => PageTableType::Invalid,
-            0b01 => PageTableType::LargePage,
-            _ => PageTableType::SmallPage,
+        PageTableType::from_raw(self.0)
+    }
+    /// Returns whether the entry is a valid translation or points to a page table
+    pub fn is_valid(self) -> bool {
+        self.get_type() != PageTableType::Invalid
+    }
+    /// Returns whether all bits of the entry are cleared, like for `new_empty`
+    ///
+    /// An invalid entry might still hold software defined bits and is not necessarily empty.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    /// Memory attributes of the page
+    pub fn attributes(self) -> MemoryAttributes {
+        MemoryAttributes::from_page_descriptor(self)
+    }
+    /// Access permission bits AP[1:0], `None` for invalid entries
+    pub fn access_permission(self) -> Option<ATTRIBUTES::AP::Value> {
+        if self.get_type() == PageTableType::Invalid {
+            return None;
+        }
+        self.attributes().access_permission()
+    }
+    /// Type extension bits, `None` for invalid entries
+    pub fn tex(self) -> Option<u8> {
+        if self.get_type() == PageTableType::Invalid {
+            return None;
         }
+        Some(self.attributes().tex())
     }
     /// Get the physical base address the page is pointing to.
     pub fn get_addr(self) -> Result<PhysicalAddress> {
@@ -680,6 +2170,42 @@ impl PageTableDescriptor {
 
 pub const PAGE_TABLE_SIZE: usize = 256; // each page is 4Kb, section is 1MB; 1Mb / 4Kb = 256
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Index into a page table that is guaranteed to be in range
+pub struct PageIndex(usize);
+
+impl PageIndex {
+    /// Create a new index, returns `None` if the index is out of range
+    pub fn new(index: usize) -> Option<Self> {
+        if index < PAGE_TABLE_SIZE {
+            Some(PageIndex(index))
+        } else {
+            None
+        }
+    }
+    /// Converts the index to an unsigned integer
+    pub fn as_usize(self) -> usize {
+        self.0
+    }
+}
+
+impl TryFrom<usize> for PageIndex {
+    type Error = PageError;
+    fn try_from(index: usize) -> Result<Self> {
+        PageIndex::new(index).ok_or(PageError::IndexError {
+            index,
+            size: PAGE_TABLE_SIZE,
+        })
+    }
+}
+
+impl From<VirtualAddress> for PageIndex {
+    fn from(addr: VirtualAddress) -> Self {
+        // The index consists of 8 bits, so it is always smaller than 256
+        PageIndex(addr.page_table_index())
+    }
+}
+
 #[repr(C)]
 #[repr(align(1024))]
 /// Second level page table memory
@@ -692,6 +2218,34 @@ impl PageTableMemory {
             table: [PageTableDescriptor::new_empty(); PAGE_TABLE_SIZE],
         }
     }
+    /// View the table as raw bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, mem::size_of::<Self>()) }
+    }
+    /// View the table as mutable raw bytes
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self as *mut Self as *mut u8, mem::size_of::<Self>()) }
+    }
+    /// Reinterpret a byte slice as table memory
+    ///
+    /// The slice has to be exactly 1024 bytes long and aligned to 1024 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<&Self> {
+        if bytes.len() != mem::size_of::<Self>() {
+            return Err(PageError::SizeError);
+        }
+        VirtualAddress::from_ptr(bytes.as_ptr()).check_align(mem::align_of::<Self>() as u32 - 1)?;
+        Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
+    }
+}
+
+// The page table has to be exactly 1KB big and aligned to 1KB
+const _: [(); PAGE_TABLE_SIZE * 4] = [(); mem::size_of::<PageTableMemory>()];
+const _: [(); 1024] = [(); mem::align_of::<PageTableMemory>()];
+
+impl Default for PageTableMemory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug)]
@@ -724,6 +2278,42 @@ impl PageTable {
         };
         Ok(page_table)
     }
+    /// Creates a page table from a reference to static memory and registers it in the translation
+    /// table at a given index
+    ///
+    /// # Safety
+    /// Any interaction with the translation table is unsafe as it might corrupt data rust is
+    /// interacting with.
+    pub unsafe fn from_static(
+        mem: &'static mut PageTableMemory,
+        mem_attributes: MemoryAttributes,
+        base_table: &mut TranslationTable,
+        index: usize,
+    ) -> Result<Self> {
+        Self::new(mem, mem_attributes, base_table, index)
+    }
+    /// Clears the page table memory and registers it in the translation table at a given index
+    ///
+    /// # Safety
+    /// Any interaction with the translation table is unsafe as it might corrupt data rust is
+    /// interacting with.
+    pub unsafe fn new_zeroed(
+        pointer: *mut PageTableMemory,
+        mem_attributes: MemoryAttributes,
+        base_table: &mut TranslationTable,
+        index: usize,
+    ) -> Result<Self> {
+        for entry in (*pointer).table.iter_mut() {
+            ptr::write_volatile(entry, PageTableDescriptor::new_empty());
+        }
+        Self::new(pointer, mem_attributes, base_table, index)
+    }
+    /// Physical address of the page table memory
+    ///
+    /// Uses the current address translation to resolve the address of the backing memory.
+    pub fn phys_addr(&self) -> Result<PhysicalAddress> {
+        get_phys_addr(VirtualAddress::from_ptr(self.pointer))
+    }
     /// Mutable reference to the page table
     ///
     /// # Safety
@@ -736,4 +2326,456 @@ impl PageTable {
     pub fn table(&self) -> &[PageTableDescriptor; PAGE_TABLE_SIZE] {
         unsafe { &(*self.pointer).table }
     }
+    /// Mutable reference to a single entry of the page table
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn entry_mut(&mut self, index: PageIndex) -> &mut PageTableDescriptor {
+        &mut (*self.pointer).table[index.0]
+    }
+    /// Immutable reference to a single entry of the page table
+    pub fn entry(&self, index: PageIndex) -> &PageTableDescriptor {
+        &self.table()[index.0]
+    }
+
+    /// Reference to the entry at `index` or `None` if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<&PageTableDescriptor> {
+        self.table().get(index)
+    }
+
+    /// Mutable reference to the entry at `index` or `None` if it is out of bounds
+    ///
+    /// # Safety
+    /// Same as `table_mut`
+    pub unsafe fn get_mut(&mut self, index: usize) -> Option<&mut PageTableDescriptor> {
+        self.table_mut().get_mut(index)
+    }
+
+    /// Number of valid entries of the page table
+    ///
+    /// Every entry maps 4KB, hence a large page is counted as 16 entries.
+    pub fn mapped_count(&self) -> usize {
+        self.table()
+            .iter()
+            .filter(|descriptor| descriptor.is_valid())
+            .count()
+    }
+}
+
+/// Read-only indexing, panics if the index is out of bounds
+///
+/// There is no `IndexMut` implementation since writing to the page table is unsafe, use
+/// `get_mut` or `entry_mut` instead.
+impl ops::Index<usize> for PageTable {
+    type Output = PageTableDescriptor;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.table()[index]
+    }
+}
+
+//
+// Heap allocated tables
+//
+//
+
+#[cfg(feature = "alloc")]
+/// Allocate zeroed memory with the alignment required by the table
+fn alloc_table<T>() -> Box<T> {
+    let layout = Layout::new::<T>();
+    unsafe {
+        let pointer = alloc::alloc::alloc_zeroed(layout) as *mut T;
+        if pointer.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        Box::from_raw(pointer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Base level translation table that owns its heap allocated memory
+pub struct OwnedTranslationTable {
+    // The table points into the memory, hence it has to be declared first
+    table: TranslationTable,
+    _memory: Box<TranslationTableMemory>,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedTranslationTable {
+    /// Allocate a new translation table with only invalid entries
+    pub fn new() -> Self {
+        let mut memory = alloc_table::<TranslationTableMemory>();
+        let table = TranslationTable::new(&mut *memory);
+        OwnedTranslationTable {
+            table,
+            _memory: memory,
+        }
+    }
+    /// Reference to the translation table
+    pub fn table(&self) -> &TranslationTable {
+        &self.table
+    }
+    /// Mutable reference to the translation table
+    pub fn table_mut(&mut self) -> &mut TranslationTable {
+        &mut self.table
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for OwnedTranslationTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for OwnedTranslationTable {
+    /// Invalidates the whole TLB before the memory is freed
+    ///
+    /// The table must not be registered in the ttbr0 or ttbr1 anymore.
+    fn drop(&mut self) {
+        asm::dsb();
+        TLBIALL.set(0);
+        asm::dsb();
+        asm::isb();
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Second level page table that owns its heap allocated memory
+///
+/// On drop the page table is removed from the translation table it was registered in.
+pub struct OwnedPageTable {
+    table: PageTable,
+    _memory: Box<PageTableMemory>,
+    base_table: *mut TranslationTableMemory,
+    index: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedPageTable {
+    /// Allocates a page table and registers it in the translation table at a given index
+    ///
+    /// # Safety
+    /// The translation table has to outlive the page table. Any interaction with the translation
+    /// table is unsafe as it might corrupt data rust is interacting with.
+    pub unsafe fn new(
+        mem_attributes: MemoryAttributes,
+        base_table: &mut TranslationTable,
+        index: usize,
+    ) -> Result<Self> {
+        if index >= TRANSLATION_TABLE_SIZE {
+            return Err(PageError::IndexError {
+                index,
+                size: TRANSLATION_TABLE_SIZE,
+            });
+        }
+        let mut memory = alloc_table::<PageTableMemory>();
+        let table = PageTable::new(&mut *memory, mem_attributes, base_table, index)?;
+        Ok(OwnedPageTable {
+            table,
+            _memory: memory,
+            base_table: base_table.pointer,
+            index,
+        })
+    }
+    /// Reference to the page table
+    pub fn table(&self) -> &PageTable {
+        &self.table
+    }
+    /// Mutable reference to the page table
+    pub fn table_mut(&mut self) -> &mut PageTable {
+        &mut self.table
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for OwnedPageTable {
+    /// Removes the page table from the translation table and invalidates the TLB entries of the
+    /// covered 1MB before the memory is freed
+    ///
+    /// Without the Multiprocessing Extensions `TLBIMVAA` is not available and the entire TLB is
+    /// invalidated instead.
+    fn drop(&mut self) {
+        unsafe {
+            (*self.base_table).table[self.index] = TranslationTableDescriptor::new_empty();
+        }
+        asm::dsb();
+        if has_multiprocessing_extensions() {
+            let section_base = (self.index as u32) << 20;
+            for page in 0..PAGE_TABLE_SIZE as u32 {
+                TLBIMVAA.set(section_base | (page << 12));
+            }
+        } else {
+            TLBIALL.set(0);
+        }
+        asm::dsb();
+        asm::isb();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::boxed::Box;
+
+    const MEMORY_TYPES: [MemoryType; 7] = [
+        MemoryType::StronglyOrdered,
+        MemoryType::SharedDevice,
+        MemoryType::NonSharedDevice,
+        MemoryType::NormalNonCacheable,
+        MemoryType::NormalWriteThrough,
+        MemoryType::NormalWriteBackNoWriteAllocate,
+        MemoryType::NormalWriteBackWriteAllocate,
+    ];
+
+    #[test]
+    fn memory_type_round_trip_without_remap() {
+        let base = MemoryAttributes::from(ATTRIBUTES::AP::PrivAccess + ATTRIBUTES::XN::Enable);
+        for &memory_type in MEMORY_TYPES.iter() {
+            let attributes = base.with_memory_type(memory_type);
+            assert_eq!(attributes.memory_type(), Some(memory_type));
+            // The builder must not touch the other attributes
+            assert_eq!(attributes.as_u32() & !0x700c, base.as_u32());
+
+            let section = TranslationTableDescriptor::new(
+                TranslationTableType::Section,
+                PhysicalAddress::new(0x8000_0000),
+                attributes,
+            )
+            .unwrap();
+            let decoded = MemoryAttributes::from_table_descriptor(section).unwrap();
+            assert_eq!(decoded.memory_type(), Some(memory_type));
+
+            for &page_type in [PageTableType::SmallPage, PageTableType::LargePage].iter() {
+                let page = PageTableDescriptor::new(
+                    page_type,
+                    PhysicalAddress::new(0x8000_0000),
+                    attributes,
+                )
+                .unwrap();
+                let decoded = MemoryAttributes::from_page_descriptor(page);
+                assert_eq!(decoded.memory_type(), Some(memory_type));
+            }
+        }
+    }
+
+    #[test]
+    fn ttbr0_offset_mapping() {
+        let mapping = OffsetMapping::new(
+            VirtualAddress::new(0xc000_0000),
+            PhysicalAddress::new(0x8000_0000),
+            0x10_0000,
+        );
+        let virt = TranslationTable::table_virt_addr(mapping, PhysicalAddress::new(0x8000_4000));
+        assert_eq!(virt.unwrap().as_u32(), 0xc000_4000);
+        // The last table in the mapping ends exactly at the end of the mapping
+        let virt = TranslationTable::table_virt_addr(mapping, PhysicalAddress::new(0x800f_c000));
+        assert_eq!(virt.unwrap().as_u32(), 0xc00f_c000);
+        // Tables that are not fully covered are rejected
+        let virt = TranslationTable::table_virt_addr(mapping, PhysicalAddress::new(0x8010_0000));
+        assert!(matches!(virt, Err(PageError::NotInRange { .. })));
+        let virt = TranslationTable::table_virt_addr(mapping, PhysicalAddress::new(0x7fff_c000));
+        assert!(matches!(virt, Err(PageError::NotInRange { .. })));
+        let mapping = OffsetMapping::new(
+            VirtualAddress::new(0xc000_0000),
+            PhysicalAddress::new(0x8000_0000),
+            0x6000,
+        );
+        let virt = TranslationTable::table_virt_addr(mapping, PhysicalAddress::new(0x8000_4000));
+        assert!(matches!(virt, Err(PageError::NotInRange { .. })));
+    }
+
+    #[test]
+    fn smp_cacheable_decode() {
+        let preset = Ttbr0Attributes::smp_cacheable();
+        let ttbr = LocalRegisterCopy::<u32, TTBR::Register>::new(preset.as_u32());
+        // IRGN = 0b01 is split into IRGN[1] in bit 0 and IRGN[0] in bit 6
+        assert_eq!(ttbr.read(TTBR::IRGN1), 0);
+        assert_eq!(ttbr.read(TTBR::IRGN0), 1);
+        assert!(ttbr.matches_all(TTBR::RGN::WriteBackWriteAllocate));
+        assert!(ttbr.matches_all(TTBR::S::Shareable));
+        assert!(ttbr.matches_all(TTBR::NOS::InnerShareable));
+        assert_eq!(ttbr.read(TTBR::IMP), 0);
+
+        let built = Ttbr0Attributes::new()
+            .inner_cacheable(CachePolicy::WriteBackWriteAllocate)
+            .outer_cacheable(CachePolicy::WriteBackWriteAllocate)
+            .inner_shareable();
+        assert_eq!(built.as_u32(), preset.as_u32());
+    }
+
+    #[test]
+    fn map_supersection_fills_all_slots() {
+        let mut memory = Box::new(TranslationTableMemory::new());
+        let mut table = TranslationTable::new(&mut *memory);
+        let phys = PhysicalAddress64::new(0x12_3400_0000);
+        unsafe { table.map_supersection(32, phys, 0x0c0c).unwrap() };
+        for index in 0..TRANSLATION_TABLE_SIZE {
+            let entry = table[index];
+            if (32..48).contains(&index) {
+                assert_eq!(entry.get_type(), TranslationTableType::Supersection);
+                assert_eq!(entry.get_addr64().unwrap().as_u64(), 0x12_3400_0000);
+            } else {
+                assert!(entry.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn map_supersection_rejects_misalignment() {
+        let mut memory = Box::new(TranslationTableMemory::new());
+        let mut table = TranslationTable::new(&mut *memory);
+        let phys = PhysicalAddress::new(0x8000_0000);
+        let result = unsafe { table.map_supersection(8, phys, 0) };
+        assert!(matches!(result, Err(PageError::AlignError { .. })));
+        let result = unsafe { table.map_supersection(TRANSLATION_TABLE_SIZE - 8, phys, 0) };
+        assert!(matches!(result, Err(PageError::IndexError { .. })));
+        let phys = PhysicalAddress::new(0x8010_0000);
+        let result = unsafe { table.map_supersection(16, phys, 0) };
+        assert!(matches!(result, Err(PageError::AlignError { .. })));
+        assert!(table.table().iter().all(|entry| entry.is_empty()));
+    }
+
+    #[test]
+    fn descriptor_predicates() {
+        let empty = TranslationTableDescriptor::new_empty();
+        assert!(empty.is_empty());
+        assert!(!empty.is_valid());
+        assert_eq!(empty, TranslationTableDescriptor::new_empty());
+        let empty = PageTableDescriptor::new_empty();
+        assert!(empty.is_empty());
+        assert!(!empty.is_valid());
+
+        let attributes = MemoryAttributes::from(ATTRIBUTES::AP::PrivAccess);
+        let section = TranslationTableDescriptor::new(
+            TranslationTableType::Section,
+            PhysicalAddress::new(0x8000_0000),
+            attributes,
+        )
+        .unwrap();
+        assert!(section.is_valid());
+        assert!(!section.is_empty());
+        assert_ne!(section, TranslationTableDescriptor::new_empty());
+
+        let page = PageTableDescriptor::new(
+            PageTableType::SmallPage,
+            PhysicalAddress::new(0x8000_1000),
+            attributes,
+        )
+        .unwrap();
+        assert!(page.is_valid());
+        assert!(!page.is_empty());
+        assert_ne!(page, PageTableDescriptor::new_empty());
+    }
+
+    #[test]
+    fn validate_tex_remap() {
+        // Section with AP = 0b01 and TEX = 0b011, reserved without TEX remapping
+        let section = TranslationTableDescriptor(0x8000_3402);
+        assert!(matches!(
+            section.validate(false),
+            Err(PageError::InvalidMemory)
+        ));
+        assert!(section.validate(true).is_ok());
+        // TEX = 0b010 with C = 1
+        let section = TranslationTableDescriptor(0x8000_2406);
+        assert!(matches!(
+            section.validate(false),
+            Err(PageError::InvalidMemory)
+        ));
+        assert!(section.validate(true).is_ok());
+        // The access permissions are checked in both cases
+        let section = TranslationTableDescriptor(0x8000_8002);
+        assert!(matches!(
+            section.validate(true),
+            Err(PageError::InvalidMemory)
+        ));
+    }
+
+    #[test]
+    fn device_mapping_range() {
+        static DEVICES: [u8; 2] = [0x10, 0x20];
+        static MANY_DEVICES: [u8; 256] = [0; 256];
+        let mut memory = Box::new(TranslationTableMemory::new());
+        let mut table = TranslationTable::new(&mut *memory);
+
+        let mapper = DeviceVmemMapper::new(VirtualAddress::new(0xff00_0000), &DEVICES).unwrap();
+        let result = unsafe { mapper.do_mapping(&mut table, false) };
+        assert!(matches!(result, Err(PageError::NotInRange { .. })));
+        let mapper = DeviceVmemMapper::new(VirtualAddress::new(0), &MANY_DEVICES).unwrap();
+        let result = unsafe { mapper.do_mapping(&mut table, true) };
+        assert!(matches!(result, Err(PageError::NotInRange { .. })));
+        assert!(table.table().iter().all(|entry| entry.is_empty()));
+
+        // The last device may end exactly at the end of the address space
+        let mapper = DeviceVmemMapper::new(VirtualAddress::new(0xfe00_0000), &DEVICES).unwrap();
+        unsafe { mapper.do_mapping(&mut table, false).unwrap() };
+        assert!(table[TRANSLATION_TABLE_SIZE - 0x20].is_valid());
+        assert!(!table[TRANSLATION_TABLE_SIZE - 0x21].is_valid());
+        // Every device covers all 16 sections of its supersection
+        assert!(table[TRANSLATION_TABLE_SIZE - 0x11].is_valid());
+        assert!(table[TRANSLATION_TABLE_SIZE - 1].is_valid());
+        assert_eq!(
+            table[TRANSLATION_TABLE_SIZE - 1]
+                .get_addr()
+                .unwrap()
+                .as_u32(),
+            0x20f0_0000
+        );
+    }
+
+    #[test]
+    fn par_decode() {
+        let virt = VirtualAddress::new(0x1234_5678);
+        let phys = decode_par(0x8765_4000 | 0x80, virt).unwrap();
+        assert_eq!(phys.as_u32(), 0x8765_4678);
+
+        // FS[4:0] in bits 1 to 5 and F in bit 0
+        let fault = |status: u32| decode_par((status << 1) | 1, virt);
+        assert!(matches!(fault(0b01001), Err(PageError::DomainError)));
+        assert!(matches!(fault(0b01011), Err(PageError::DomainError)));
+        assert!(matches!(fault(0b01101), Err(PageError::PermissionError)));
+        assert!(matches!(fault(0b01111), Err(PageError::PermissionError)));
+        assert!(matches!(fault(0b00101), Err(PageError::TranslationError)));
+        assert!(matches!(fault(0b00111), Err(PageError::TranslationError)));
+        // FS[4] is part of the status, 0b11101 is not a permission fault
+        assert!(matches!(fault(0b11101), Err(PageError::TranslationError)));
+        // The external abort type in bit 6 does not change the status
+        let external = decode_par((0b01101 << 1) | 0x40 | 1, virt);
+        assert!(matches!(external, Err(PageError::PermissionError)));
+    }
+
+    #[test]
+    fn memory_type_round_trip_with_remap() {
+        // With TEX remapping the type is only an index into PRRR and NMRR given by TEX[0], C, and B
+        for &memory_type in MEMORY_TYPES.iter() {
+            let attributes = MemoryAttributes::default().with_memory_type(memory_type);
+            let bits = memory_type.bits();
+            let expected = (((bits >> 12) & 1) << 2) | ((bits >> 2) & 0b11);
+            assert_eq!(attributes.remap_index() as u32, expected);
+
+            let section = TranslationTableDescriptor::new(
+                TranslationTableType::Section,
+                PhysicalAddress::new(0x8000_0000),
+                attributes,
+            )
+            .unwrap();
+            let decoded = MemoryAttributes::from_table_descriptor(section).unwrap();
+            assert_eq!(decoded.remap_index(), attributes.remap_index());
+        }
+        // Every index is reachable through the builder of the attributes
+        let mut seen = [false; 8];
+        for index in 0..8u32 {
+            let attributes = MemoryAttributes::from(
+                ATTRIBUTES::TEX.val(index >> 2)
+                    + ATTRIBUTES::C.val((index >> 1) & 1)
+                    + ATTRIBUTES::B.val(index & 1),
+            );
+            seen[attributes.remap_index() as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
 }
@@ -0,0 +1,83 @@
+//! On-target self-test of the paging structures
+//!
+//! The test can only run on hardware or in an emulator. It covers the descriptor construction,
+//! the installation as ttbr0, enabling the MMU, and the address translation in one call.
+
+use crate::asm;
+use crate::regs::tlb_maintenance::*;
+use crate::regs::vmem_control::*;
+use crate::structures::paging::*;
+use crate::{PhysicalAddress, VirtualAddress};
+
+static mut SELFTEST_TABLE: TranslationTableMemory = TranslationTableMemory::new();
+
+/// Domain used for the identity map
+const SELFTEST_DOMAIN: u8 = 0;
+
+/// Build an identity map, enable the MMU with it, and check the translation of known addresses
+///
+/// The whole address space is identity mapped with strongly ordered sections in domain 0. The
+/// caches are disabled during the test. Afterwards the previous SCTLR, TTBR0, and DACR are
+/// restored and the TLB is invalidated.
+///
+/// # Safety
+/// The MMU has to be disabled or the code, the stack, and the static memory have to be identity
+/// mapped. No other core may use the translation tables or the MMU configuration of this core
+/// and interrupts should be masked during the test.
+pub unsafe fn run_identity_map_selftest() -> Result<()> {
+    let saved_sctlr = SCTLR.get();
+    let saved_ttbr0 = TTBR0.get();
+    let saved_dacr = DACR.get();
+
+    disable_caches();
+    disable_mmu();
+
+    let result = identity_map_selftest();
+
+    disable_mmu();
+    TTBR0.set(saved_ttbr0);
+    DACR.set(saved_dacr);
+    TLBIALL.set(0);
+    asm::dsb();
+    SCTLR.set(saved_sctlr);
+    asm::isb();
+    result
+}
+
+unsafe fn identity_map_selftest() -> Result<()> {
+    let table_memory: *mut TranslationTableMemory = &mut SELFTEST_TABLE;
+    let mut table = TranslationTable::new_zeroed(table_memory);
+    let attributes = MemoryAttributes::from(
+        ATTRIBUTES::AP::FullAccess + ATTRIBUTES::DOMAIN.val(SELFTEST_DOMAIN as u32),
+    );
+    for index in 0..TRANSLATION_TABLE_SIZE {
+        let addr = VirtualAddress::new((index as u32) << 20);
+        let descriptor = TranslationTableDescriptor::new(
+            TranslationTableType::Section,
+            PhysicalAddress::new(addr.as_u32()),
+            attributes,
+        )?;
+        table.map_entry(TableIndex::from(addr), descriptor, false)?;
+    }
+    set_domain_access(SELFTEST_DOMAIN, DomainAccess::Client)?;
+    table.set_as_ttbr0()?;
+    enable_mmu();
+
+    let local = 0u32;
+    let known_addresses = [
+        VirtualAddress::from_ptr(table_memory),
+        VirtualAddress::from_ptr(&local),
+        VirtualAddress::from_ptr(identity_map_selftest as *const ()),
+    ];
+    for &addr in known_addresses.iter() {
+        let phys = get_phys_addr(addr)?;
+        if phys.as_u32() != addr.as_u32() {
+            return Err(PageError::MappingMismatch {
+                virt: addr.as_u32(),
+                expected: addr.as_u32(),
+                actual: phys.as_u32(),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Module for stacks with guard pages
+//!
+//! Reserve the lowest page of a stack region as guard page by
+//! ```
+//!     let stack = StackLayout::new(stack_base, 0x4000)?;
+//!     unsafe { stack.install_guard(&mut page_table) };
+//! ```
+//! and check in the data abort handler whether the fault was a stack overflow by
+//! ```
+//!     if stack.is_guard_data_abort() { ... }
+//! ```
+
+use crate::regs::fault_handling::*;
+use crate::structures::paging::*;
+use crate::VirtualAddress;
+
+#[derive(Copy, Clone, Debug)]
+/// A stack region whose lowest page is used as guard page
+pub struct StackLayout {
+    base: VirtualAddress,
+    size: u32,
+}
+
+impl StackLayout {
+    /// Create a new stack layout
+    ///
+    /// `base` is the lowest address of the region and has to be aligned to 4KB, `size` has to be a
+    /// multiple of 4KB and include at least one page in addition to the guard page.
+    pub fn new(base: VirtualAddress, size: u32) -> Result<Self> {
+        if (base.as_u32() & (PAGE_SIZE - 1)) != 0 {
+            return Err(PageError::AlignError {
+                addr: base.as_u32(),
+                align: PAGE_SIZE,
+            });
+        }
+        // A size that is not a multiple of 4KB leaves the top of the stack unaligned
+        if (size & (PAGE_SIZE - 1)) != 0 {
+            return Err(PageError::AlignError {
+                addr: base.as_u32().wrapping_add(size),
+                align: PAGE_SIZE,
+            });
+        }
+        if size < 2 * PAGE_SIZE || base.as_u32().checked_add(size).is_none() {
+            return Err(PageError::SizeError);
+        }
+        Ok(StackLayout { base, size })
+    }
+    /// Address of the guard page
+    pub fn guard_page(&self) -> VirtualAddress {
+        self.base
+    }
+    /// Lowest usable address of the stack
+    pub fn stack_bottom(&self) -> VirtualAddress {
+        self.base + PAGE_SIZE
+    }
+    /// Initial stack pointer, i.e. the address above the stack
+    pub fn stack_top(&self) -> VirtualAddress {
+        self.base + self.size
+    }
+    /// Remove the mapping of the guard page
+    ///
+    /// # Safety
+    /// The page table has to be the one that translates the guard page. The caller has to
+    /// invalidate the TLB entry of the guard page afterwards.
+    pub unsafe fn install_guard(&self, page_table: &mut PageTable) {
+        page_table.table_mut()[self.base.page_table_index()] = PageTableDescriptor::new_empty();
+    }
+    /// Checks whether a fault address lies in the guard page
+    pub fn is_guard_fault(&self, fault_addr: VirtualAddress) -> bool {
+        let addr = fault_addr.as_u32();
+        addr >= self.base.as_u32() && addr < self.base.as_u32() + PAGE_SIZE
+    }
+    /// Checks whether the current data abort was caused by an access to the guard page
+    pub fn is_guard_data_abort(&self) -> bool {
+        self.is_guard_fault(VirtualAddress::new(DFAR.get()))
+    }
+}
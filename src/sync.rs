@@ -0,0 +1,105 @@
+//! Atomic operations based on the exclusive monitor
+//!
+//! All operations are sequentially consistent, i.e. they are surrounded by data memory barriers.
+//!
+//! Increment a 64 bit counter by
+//! ```
+//!     let old = unsafe { atomic_add_u64(&mut TICKS, 1) };
+//! ```
+
+use crate::asm;
+use crate::regs::vmem_control::{RegisterReadWrite, SCTLR};
+
+/// Atomically add to a 64 bit value and return the previous value
+///
+/// # Safety
+/// `ptr` has to be valid and aligned to 8 bytes.
+pub unsafe fn atomic_add_u64(ptr: *mut u64, val: u64) -> u64 {
+    let old: u64;
+    let _new: u64;
+    let _status: u32;
+    asm::dmb();
+    // A 64 bit operand is allocated to an even/odd register pair as required by ldrexd and
+    // strexd, ${n:H} is the odd (high) register of the pair
+    llvm_asm!("1:
+               ldrexd $0, ${0:H}, [$3]
+               adds $1, $0, $4
+               adc ${1:H}, ${0:H}, ${4:H}
+               strexd $2, $1, ${1:H}, [$3]
+               teq $2, #0
+               bne 1b"
+              : "=&r"(old), "=&r"(_new), "=&r"(_status)
+              : "r"(ptr), "r"(val)
+              : "cc", "memory"
+              : "volatile");
+    asm::dmb();
+    old
+}
+
+/// Atomically replace a 64 bit value if it equals `current`
+///
+/// Returns the previous value, as `Ok` if it was replaced and as `Err` otherwise.
+///
+/// # Safety
+/// `ptr` has to be valid and aligned to 8 bytes.
+pub unsafe fn atomic_compare_exchange_u64(
+    ptr: *mut u64,
+    current: u64,
+    new: u64,
+) -> Result<u64, u64> {
+    let old: u64;
+    let _status: u32;
+    asm::dmb();
+    llvm_asm!("1:
+               ldrexd $0, ${0:H}, [$2]
+               teq $0, $3
+               teqeq ${0:H}, ${3:H}
+               bne 2f
+               strexd $1, $4, ${4:H}, [$2]
+               teq $1, #0
+               bne 1b
+               2:
+               clrex"
+              : "=&r"(old), "=&r"(_status)
+              : "r"(ptr), "r"(current), "r"(new)
+              : "cc", "memory"
+              : "volatile");
+    asm::dmb();
+    if old == current {
+        Ok(old)
+    } else {
+        Err(old)
+    }
+}
+
+/// Atomically replace a 32 bit value and return the previous value
+///
+/// This is the replacement for the deprecated `swp` instruction and works regardless of SCTLR.SW.
+/// Prefer it when porting code using `swp` and leave SWP disabled.
+///
+/// # Safety
+/// `ptr` has to be valid and aligned to 4 bytes.
+pub unsafe fn swap(ptr: *mut u32, val: u32) -> u32 {
+    let old: u32;
+    let _status: u32;
+    asm::dmb();
+    llvm_asm!("1:
+               ldrex $0, [$2]
+               strex $1, $3, [$2]
+               teq $1, #0
+               bne 1b"
+              : "=&r"(old), "=&r"(_status)
+              : "r"(ptr), "r"(val)
+              : "cc", "memory"
+              : "volatile");
+    asm::dmb();
+    old
+}
+
+/// Returns whether the `swp` and `swpb` instructions are enabled
+///
+/// If SCTLR.SW is clear, the instructions are undefined on cores with the Multiprocessing
+/// Extensions.
+pub fn swp_enabled() -> bool {
+    SCTLR.is_set(SCTLR::SW)
+}
@@ -0,0 +1,85 @@
+//! Small helpers used by the other modules
+//!
+//! # Usage examples
+//! Iterate over the domains used by a translation table
+//! ```
+//!     for domain in table.domains_used_iter() {
+//!         // configure the domain
+//!     }
+//! ```
+
+use core::fmt;
+
+/// Formats a word as `0x1234_5678` in `Debug` output
+pub(crate) struct HexWord(pub u32);
+
+impl fmt::Debug for HexWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x}_{:04x}", self.0 >> 16, self.0 & 0xffff)
+    }
+}
+
+/// Formats a double word as `0x12_3456_7890` in `Debug` output, without leading zero groups
+pub(crate) struct HexDoubleWord(pub u64);
+
+impl fmt::Debug for HexDoubleWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let high = (self.0 >> 32) as u32;
+        let low = self.0 as u32;
+        if high == 0 {
+            return HexWord(low).fmt(f);
+        }
+        if high >> 16 != 0 {
+            write!(f, "{:#x}_{:04x}", high >> 16, high & 0xffff)?;
+        } else {
+            write!(f, "{:#x}", high)?;
+        }
+        write!(f, "_{:04x}_{:04x}", low >> 16, low & 0xffff)
+    }
+}
+
+/// Iterator over the indices of the set bits of a mask, starting with the lowest bit
+#[derive(Copy, Clone, Debug)]
+pub struct BitMaskIter {
+    mask: u32,
+}
+
+impl BitMaskIter {
+    /// Create an iterator over the set bits of `mask`
+    pub const fn new(mask: u32) -> Self {
+        BitMaskIter { mask }
+    }
+}
+
+impl From<u16> for BitMaskIter {
+    fn from(mask: u16) -> Self {
+        Self::new(mask as u32)
+    }
+}
+
+impl From<u32> for BitMaskIter {
+    fn from(mask: u32) -> Self {
+        Self::new(mask)
+    }
+}
+
+impl Iterator for BitMaskIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = self.mask.trailing_zeros();
+        // Clear the lowest set bit
+        self.mask &= self.mask - 1;
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.mask.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for BitMaskIter {}